@@ -0,0 +1,31 @@
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::exit;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl-C set a cancellation flag instead of killing the process, so that long running
+/// loops stop and flush output produced so far. A second Ctrl-C exits right away, for anything
+/// blocked where the flag isn't checked.
+pub fn install_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if CANCELLED.swap(true, Ordering::Relaxed) {
+            process::exit(exit::INTERRUPTED);
+        }
+    })
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::Relaxed)
+}
+
+/// Fails with `Interrupted` error once cancelled, for loops to return early with `?`
+pub fn check() -> io::Result<()> {
+    if is_cancelled() {
+        Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+    } else {
+        Ok(())
+    }
+}
@@ -1,24 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory};
 pub use clap::{Parser, Subcommand};
 use thiserror::Error;
 
+use crate::color::ColorWhen;
+use crate::config::{Config, ConfigError};
+use crate::plugin;
 use crate::{
-    EchoArgs, EchoError, GrepArgs, GrepError, HexArgs, ViewArgs, ViewError, hex::HexError,
+    CmpArgs, CmpError, EchoArgs, EchoError, FindArgs, FoldArgs, GrepArgs, GrepError, HeadArgs,
+    HexArgs, JoinArgs, MangenArgs, NlArgs, PasteArgs, ReplaceArgs, TailArgs, TeeArgs, ViewArgs,
+    ViewError, hex::HexError, view::RangeSpec,
 };
 
 pub type Result<T> = std::result::Result<T, CliError>;
 
+/// `kf --version` output, build facts are gathered by build script so bug reports name exact build
+const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit: ",
+    env!("KF_GIT_COMMIT"),
+    "\nbuild date: ",
+    env!("KF_BUILD_DATE"),
+    "\ntarget: ",
+    env!("KF_TARGET"),
+    "\nfeatures: ",
+    env!("KF_FEATURES"),
+);
+
 #[derive(Parser, Debug)]
 #[command(
     name = "kf",
-    version = "0.1",
+    version,
+    long_version = LONG_VERSION,
     about = "file view / search tool",
-    author = "kos"
+    author = "kos",
+    // defaults from config file come first and are overridden by repeated options
+    args_override_self = true
 )]
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = ColorWhen::Auto,
+        num_args = 0..=1,
+        default_missing_value = "always",
+        help = "Color output, 'auto' colors terminal output unless NO_COLOR is set or CLICOLOR_FORCE forces it"
+    )]
+    pub color: ColorWhen,
+
+    // '-v' and '--verbose' are taken by grep and view
+    #[arg(
+        short = 'L',
+        long = "log",
+        global = true,
+        action = ArgAction::Count,
+        help = "Log phases with timing to standard error, repeat to log every opened or skipped file"
+    )]
+    pub log: u8,
+
+    // no short '-o', grep uses it for --only-matching
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Write output to file instead of standard output"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "output",
+        help = "Append to output file instead of overwriting it"
+    )]
+    pub append: bool,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "output",
+        conflicts_with = "append",
+        help = "Write to a temporary file renamed over output file only when command succeeds"
+    )]
+    pub atomic: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Show what a command that writes files, like 'hex --patch', would change without writing"
+    )]
+    pub dry_run: bool,
+
+    // checked on raw arguments before parsing, see `plugin::requested_plugin`
+    #[arg(
+        long,
+        global = true,
+        help = "Never run unknown commands as 'kf-<name>' plugins found on PATH"
+    )]
+    pub no_plugins: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+impl Cli {
+    /// Parses command line arguments with default arguments of config file merged beneath them
+    pub fn parse_with_config(
+        args: Vec<OsString>,
+        config: &Config,
+    ) -> std::result::Result<Self, clap::Error> {
+        let args = infer_command(args);
+        let mut cli = Self::try_parse_from(with_default_args(args, config))?;
+        cli.command
+            .resolve_range_presets(&config.ranges)
+            .map_err(|e| Self::command().error(ErrorKind::InvalidValue, e))?;
+
+        // global option can't be named in conflicts of subcommand arguments
+        let output_conflict = match &cli.command {
+            Command::Echo(args) if args.stderr => Some("--stderr"),
+            Command::View(args) if args.watch => Some("--watch"),
+            _ => None,
+        };
+        if let Some(arg) = output_conflict
+            && cli.output.is_some()
+        {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                format!("the argument '{}' cannot be used with '--output'", arg),
+            ));
+        }
+
+        // `--output` writes a file for any command
+        if cli.dry_run && cli.output.is_none() && !cli.command.writes_files() {
+            return Err(Self::command().error(
+                ErrorKind::ArgumentConflict,
+                "the argument '--dry-run' only applies to commands that write files, like 'hex --patch', or with '--output'",
+            ));
+        }
+
+        Ok(cli)
+    }
+}
+
+/// Replaces `@FILE` arguments with lines of FILE, for argument lists longer than the platform
+/// allows on a command line. Like gcc, `@word` naming no file is kept as is, so `-n @preset`
+/// range presets still work. Arguments after `--` and lines of argument files aren't expanded.
+pub fn expand_arg_files(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let mut expanded = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    expanded.extend(args.next());
+
+    for arg in args.by_ref() {
+        if arg == "--" {
+            expanded.push(arg);
+            break;
+        }
+        let arg_file = arg
+            .to_str()
+            .and_then(|arg| arg.strip_prefix('@'))
+            .map(PathBuf::from)
+            .filter(|path| path.is_file());
+        let Some(path) = arg_file else {
+            expanded.push(arg);
+            continue;
+        };
+
+        let content = fs::read_to_string(&path).map_err(|source| CliError::ArgFile {
+            path: path.clone(),
+            source,
+        })?;
+        expanded.extend(content.lines().filter(|line| !line.trim().is_empty()).map(OsString::from));
+    }
+
+    expanded.extend(args);
+    Ok(expanded)
+}
+
+/// Executable of plugin that unknown command of arguments refers to, with arguments passed to it
+pub fn find_plugin(args: &[OsString], config: &Config) -> Option<(PathBuf, Vec<OsString>)> {
+    let name = plugin::requested_plugin(args, &config.plugins, |name| {
+        is_subcommand(name) || Path::new(name).is_file()
+    })?;
+    let path = plugin::find_plugin(name)?;
+    let index = subcommand_index(args)?;
+    Some((path, args[index + 1..].to_vec()))
+}
+
+/// Position of subcommand name in `args`, global options like `--color never` may come first.
+/// Like clap, an option with optional value takes next argument unless it starts with '-'.
+pub fn subcommand_index(args: &[OsString]) -> Option<usize> {
+    let mut command = Cli::command();
+    command.build();
+    let global_args: Vec<_> = command.get_arguments().collect();
+    let value_of = |arg: Option<&&clap::Arg>| {
+        let arg = arg.filter(|arg| arg.get_action().takes_values())?;
+        Some(arg.get_num_args().is_some_and(|n| n.min_values() == 0))
+    };
+
+    let mut index = 1;
+    while let Some(arg) = args.get(index).and_then(|arg| arg.to_str()) {
+        let takes_value = if let Some(long) = arg.strip_prefix("--") {
+            if long.is_empty() {
+                return None;
+            }
+            // value given as '--output=PATH' is part of the argument
+            let found = global_args.iter().find(|arg| arg.get_long() == Some(long));
+            value_of(found)
+        } else if let Some(shorts) = arg.strip_prefix('-')
+            && let Some(short) = shorts.chars().next()
+        {
+            let found = global_args.iter().find(|arg| arg.get_short() == Some(short));
+            value_of(found).filter(|_| shorts.len() == short.len_utf8())
+        } else {
+            return Some(index);
+        };
+
+        index += 1;
+        match takes_value {
+            Some(false) => index += 1,
+            Some(true) => {
+                let next = args.get(index).and_then(|arg| arg.to_str());
+                if next.is_some_and(|next| !next.starts_with('-')) {
+                    index += 1;
+                }
+            }
+            None => {}
+        }
+    }
+    args.get(index).map(|_| index)
+}
+
+fn is_subcommand(name: &str) -> bool {
+    name == "help" || Cli::command().get_subcommands().any(|command| command.get_name() == name)
+}
+
+/// `kf FILE...` is taken as `kf view FILE...` when first argument is an existing file and not a
+/// subcommand name
+fn infer_command(mut args: Vec<OsString>) -> Vec<OsString> {
+    let Some(index) = subcommand_index(&args) else {
+        return args;
+    };
+    let first = &args[index];
+    let is_command = first.to_str().is_some_and(is_subcommand);
+    if !is_command && Path::new(first).is_file() {
+        args.insert(index, OsString::from("view"));
+    }
+    args
+}
+
+/// Inserts config defaults of subcommand right after its name. Options given on command line,
+/// before or after subcommand, are left out of defaults, so that they replace config values
+/// instead of being overridden by them or appended to them like `view -n` ranges.
+fn with_default_args(mut args: Vec<OsString>, config: &Config) -> Vec<OsString> {
+    let Some(index) = subcommand_index(&args) else {
+        return args;
+    };
+    let Some(command) = args[index].to_str() else {
+        return args;
+    };
+    let mut cli_command = Cli::command();
+    cli_command.build();
+    let Some(subcommand) = cli_command.find_subcommand(command) else {
+        return args;
+    };
+    let given = given_arg_ids(&args);
+    let defaults = without_given_args(config.default_args(command), subcommand, &given);
+    args.splice(
+        index + 1..index + 1,
+        defaults.into_iter().map(OsString::from),
+    );
+    args
+}
+
+/// Ids of arguments given on command line, none when it doesn't parse without defaults
+fn given_arg_ids(args: &[OsString]) -> HashSet<String> {
+    let Ok(matches) = Cli::command().try_get_matches_from(args) else {
+        return HashSet::new();
+    };
+    let from_command_line = |matches: &ArgMatches| {
+        matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut ids: HashSet<_> = from_command_line(&matches).into_iter().collect();
+    if let Some((_, matches)) = matches.subcommand() {
+        ids.extend(from_command_line(matches));
+    }
+    ids
+}
+
+/// Default arguments without options of `given` ids, each option is dropped with its value
+fn without_given_args(
+    defaults: Vec<String>,
+    command: &clap::Command,
+    given: &HashSet<String>,
+) -> Vec<String> {
+    let find_arg = |token: &str| {
+        if let Some(long) = token.strip_prefix("--") {
+            let name = long.split_once('=').map_or(long, |(name, _)| name);
+            command.get_arguments().find(|arg| {
+                arg.get_long() == Some(name)
+                    || arg.get_all_aliases().is_some_and(|aliases| aliases.contains(&name))
+            })
+        } else {
+            let short = token.strip_prefix('-')?.chars().next()?;
+            command.get_arguments().find(|arg| {
+                arg.get_short() == Some(short)
+                    || arg.get_all_short_aliases().is_some_and(|aliases| aliases.contains(&short))
+            })
+        }
+    };
+
+    let mut kept = vec![];
+    let mut tokens = defaults.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let Some(arg) = find_arg(&token) else {
+            kept.push(token);
+            continue;
+        };
+        // value in next token unless joined like '--columns=8' or '-n1..2'
+        let joined = token.contains('=') || (!token.starts_with("--") && token.len() > 2);
+        let optional = arg.get_num_args().is_some_and(|n| n.min_values() == 0);
+        let value = tokens
+            .next_if(|next| {
+                arg.get_action().takes_values() && !joined && !(optional && next.starts_with('-'))
+            })
+            .into_iter();
+        if !given.contains(arg.get_id().as_str()) {
+            kept.push(token);
+            kept.extend(value);
+        }
+    }
+    kept
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Searches pattern in target files or directories
@@ -29,13 +355,151 @@ pub enum Command {
     Echo(EchoArgs),
     /// View file in hex format
     Hex(HexArgs),
+    /// Print first lines of files
+    Head(HeadArgs),
+    /// Print last lines of files, optionally following appended lines
+    Tail(TailArgs),
+    /// Number lines of files
+    Nl(NlArgs),
+    /// Find files and directories by name, type, size or modification time
+    Find(FindArgs),
+    /// Copy standard input to standard output and files
+    Tee(TeeArgs),
+    /// Wrap long lines to a width, or reflow paragraphs
+    Fold(FoldArgs),
+    /// Compare two files byte by byte
+    Cmp(CmpArgs),
+    /// Replace regex matches in files in place, or in standard input
+    Replace(ReplaceArgs),
+    /// Merge corresponding lines of files into columns
+    Paste(PasteArgs),
+    /// Join lines of two sorted files on a common field
+    Join(JoinArgs),
+    /// Generate man pages from command definitions
+    #[command(hide = true)]
+    Mangen(MangenArgs),
+}
+
+impl Command {
+    /// Passes global `--color` to command arguments
+    pub fn set_color(&mut self, color: ColorWhen) {
+        match self {
+            Command::Grep(args) => args.color = color,
+            Command::View(args) => args.color = color,
+            Command::Echo(args) => args.color = color,
+            Command::Hex(args) => args.color = color,
+            Command::Replace(args) => args.color = color,
+            Command::Head(_)
+            | Command::Tail(_)
+            | Command::Nl(_)
+            | Command::Find(_)
+            | Command::Tee(_)
+            | Command::Fold(_)
+            | Command::Cmp(_)
+            | Command::Paste(_)
+            | Command::Join(_)
+            | Command::Mangen(_) => {}
+        }
+    }
+
+    /// Whether command changes files, every such command must preview its changes instead
+    /// under global `--dry-run`, see `set_dry_run`
+    pub fn writes_files(&self) -> bool {
+        match self {
+            Command::Hex(args) => !args.patch.is_empty(),
+            Command::Mangen(args) => args.out_dir.is_some(),
+            Command::Tee(args) => !args.files.is_empty(),
+            Command::Replace(args) => !args.paths.is_empty(),
+            Command::Grep(_)
+            | Command::View(_)
+            | Command::Echo(_)
+            | Command::Head(_)
+            | Command::Tail(_)
+            | Command::Nl(_)
+            | Command::Find(_)
+            | Command::Fold(_)
+            | Command::Cmp(_)
+            | Command::Paste(_)
+            | Command::Join(_) => false,
+        }
+    }
+
+    /// Whether command stops its long running loops by `cancel::check`, Ctrl-C is taken over
+    /// only for these, any other command is killed by it right away
+    pub fn polls_cancel(&self) -> bool {
+        match self {
+            Command::View(args) => args.follow || args.watch,
+            Command::Tail(args) => args.follow || args.follow_retry,
+            Command::Grep(_)
+            | Command::Echo(_)
+            | Command::Hex(_)
+            | Command::Find(_)
+            | Command::Cmp(_)
+            | Command::Replace(_) => true,
+            Command::Head(_)
+            | Command::Nl(_)
+            | Command::Tee(_)
+            | Command::Fold(_)
+            | Command::Paste(_)
+            | Command::Join(_)
+            | Command::Mangen(_) => false,
+        }
+    }
+
+    /// Resolves `@name` range presets of range arguments by `[ranges]` table of config file
+    pub fn resolve_range_presets(
+        &mut self,
+        presets: &HashMap<String, String>,
+    ) -> std::result::Result<(), String> {
+        let specs: Vec<&mut RangeSpec> = match self {
+            Command::View(args) => {
+                args.lines.iter_mut().chain(&mut args.bytes).chain(&mut args.chars).collect()
+            }
+            Command::Hex(args) => args.bytes.iter_mut().collect(),
+            Command::Grep(args) => vec![&mut args.lines],
+            Command::Echo(_)
+            | Command::Head(_)
+            | Command::Tail(_)
+            | Command::Nl(_)
+            | Command::Find(_)
+            | Command::Tee(_)
+            | Command::Fold(_)
+            | Command::Cmp(_)
+            | Command::Replace(_)
+            | Command::Paste(_)
+            | Command::Join(_)
+            | Command::Mangen(_) => vec![],
+        };
+        specs.into_iter().try_for_each(|spec| spec.resolve_presets(presets))
+    }
+
+    /// Passes global `--dry-run` to commands that write files
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        match self {
+            Command::Hex(args) => args.dry_run = dry_run,
+            Command::Mangen(args) => args.dry_run = dry_run,
+            Command::Tee(args) => args.dry_run = dry_run,
+            Command::Replace(args) => args.dry_run = dry_run,
+            Command::Grep(_)
+            | Command::View(_)
+            | Command::Echo(_)
+            | Command::Head(_)
+            | Command::Tail(_)
+            | Command::Nl(_)
+            | Command::Find(_)
+            | Command::Fold(_)
+            | Command::Cmp(_)
+            | Command::Paste(_)
+            | Command::Join(_) => {}
+        }
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum CliError {
-    // TODO: need dynamic error type
-    #[error("{0}")]
-    Usage(String),
+    /// also `--help` and `--version`, which clap reports as errors printed to stdout
+    #[error(transparent)]
+    Usage(#[from] clap::Error),
 
     #[error(transparent)]
     Grep(#[from] GrepError),
@@ -48,4 +512,171 @@ pub enum CliError {
 
     #[error(transparent)]
     Hex(#[from] HexError),
+
+    #[error(transparent)]
+    Cmp(#[from] CmpError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("cannot write {}: {source}", path.display())]
+    Output { path: PathBuf, source: io::Error },
+
+    #[error("cannot read argument file {}: {source}", path.display())]
+    ArgFile { path: PathBuf, source: io::Error },
+
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl CliError {
+    /// Output reader like `head` or a pager quit before reading all output, which is how
+    /// pipelines normally end and not an error
+    pub fn is_broken_pipe(&self) -> bool {
+        self.io_error().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+    }
+
+    /// Command stopped early by Ctrl-C, see `cancel`
+    pub fn is_interrupted(&self) -> bool {
+        self.io_error().is_some_and(|e| e.kind() == io::ErrorKind::Interrupted)
+    }
+
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self {
+            CliError::Grep(GrepError::IoError(e))
+            | CliError::View(ViewError::IoError(e))
+            | CliError::Echo(EchoError::IoError(e))
+            | CliError::Hex(HexError::IoError(e))
+            | CliError::Cmp(CmpError::IoError(e))
+            | CliError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_command() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            infer_command(args(&["kf", "Cargo.toml", "-n", "1"])),
+            args(&["kf", "view", "Cargo.toml", "-n", "1"])
+        );
+        assert_eq!(
+            infer_command(args(&["kf", "hex", "Cargo.toml"])),
+            args(&["kf", "hex", "Cargo.toml"])
+        );
+        assert_eq!(
+            infer_command(args(&["kf", "missing.txt"])),
+            args(&["kf", "missing.txt"])
+        );
+        assert_eq!(infer_command(args(&["kf", "src"])), args(&["kf", "src"]));
+        assert_eq!(
+            infer_command(args(&["kf", "--color", "never", "-L", "Cargo.toml"])),
+            args(&["kf", "--color", "never", "-L", "view", "Cargo.toml"])
+        );
+    }
+
+    #[test]
+    fn test_with_default_args() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        let config: Config = toml::from_str(
+            "color = \"never\"\n[view]\nargs = [\"-n\", \"1..2\", \"--max-line-bytes=8\"]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            with_default_args(
+                args(&["kf", "--color=always", "grep", "a", "f.txt"]),
+                &config
+            ),
+            args(&["kf", "--color=always", "grep", "a", "f.txt"])
+        );
+        assert_eq!(
+            with_default_args(args(&["kf", "grep", "a", "f.txt"]), &config),
+            args(&["kf", "grep", "--color=never", "a", "f.txt"])
+        );
+        assert_eq!(
+            with_default_args(args(&["kf", "view", "-n", "4..5", "f.txt"]), &config),
+            args(&[
+                "kf",
+                "view",
+                "--color=never",
+                "--max-line-bytes=8",
+                "-n",
+                "4..5",
+                "f.txt"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_range_presets() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        let config: Config = toml::from_str("[ranges]\nheader = \"..20\"\n").unwrap();
+
+        let cli = Cli::parse_with_config(args(&["kf", "view", "-n", "@header", "f"]), &config);
+        match cli.unwrap().command {
+            Command::View(args) => assert_eq!(args.lines, [RangeSpec::To(20)]),
+            command => panic!("unexpected command {:?}", command),
+        }
+        let error = Cli::parse_with_config(args(&["kf", "view", "-n", "@missing", "f"]), &config);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_subcommand_index() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(subcommand_index(&args(&["kf", "hex", "f"])), Some(1));
+        assert_eq!(
+            subcommand_index(&args(&["kf", "--color", "never", "hex"])),
+            Some(3)
+        );
+        assert_eq!(
+            subcommand_index(&args(&["kf", "--color", "-L", "grep"])),
+            Some(3)
+        );
+        assert_eq!(
+            subcommand_index(&args(&["kf", "--color=never", "-LL", "grep"])),
+            Some(3)
+        );
+        assert_eq!(
+            subcommand_index(&args(&["kf", "--output", "out", "echo"])),
+            Some(3)
+        );
+        assert_eq!(subcommand_index(&args(&["kf", "--output"])), None);
+        assert_eq!(subcommand_index(&args(&["kf", "--dry-run"])), None);
+    }
+
+    #[test]
+    fn test_expand_arg_files() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        let arg_file = std::env::temp_dir().join("kf_expand_arg_files.txt");
+        fs::write(&arg_file, "-n\r\n\nfoo bar.txt\n").unwrap();
+        let arg = format!("@{}", arg_file.display());
+
+        assert_eq!(
+            expand_arg_files(args(&[
+                "kf", "grep", "x", &arg, "-n", "@recent", "--", &arg
+            ]))
+            .unwrap(),
+            args(&[
+                "kf",
+                "grep",
+                "x",
+                "-n",
+                "foo bar.txt",
+                "-n",
+                "@recent",
+                "--",
+                &arg
+            ])
+        );
+        fs::remove_file(arg_file).unwrap();
+    }
 }
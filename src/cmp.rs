@@ -0,0 +1,209 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use thiserror::Error;
+
+use crate::cancel;
+use crate::exit::{self, ExitCode};
+use crate::hex::read_full;
+use crate::paths;
+
+/// bytes of each file compared at once
+const BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Parser)]
+pub struct CmpArgs {
+    #[arg(index = 1, help = "First file, standard input when '-'")]
+    pub left: PathBuf,
+
+    #[arg(index = 2, help = "Second file, standard input when '-'")]
+    pub right: PathBuf,
+
+    #[arg(
+        short = 'l',
+        long,
+        help = "List every differing byte with its 1-based number and both values in hex"
+    )]
+    pub list: bool,
+
+    #[arg(
+        short = 's',
+        long,
+        conflicts_with = "list",
+        help = "Print nothing, only exit status tells whether files differ"
+    )]
+    pub silent: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum CmpError {
+    /// failure to write output
+    #[error("{0}")]
+    IoError(#[from] io::Error),
+
+    #[error("cannot read {}: {source}", path.display())]
+    ReadFailed { path: PathBuf, source: io::Error },
+
+    /// files differ, like `cmp` it's reported by exit code 1
+    #[error("files differ")]
+    Differ,
+}
+
+impl ExitCode for CmpError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CmpError::IoError(_) | CmpError::ReadFailed { .. } => exit::IO,
+            CmpError::Differ => exit::NOT_FOUND,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, CmpError>;
+
+pub fn cmp(args: CmpArgs) -> Result<()> {
+    cmp_to(&args, &mut io::stdout().lock())
+}
+
+/// Compares two files byte by byte, `Differ` error tells they aren't equal
+pub fn cmp_to<W: Write>(args: &CmpArgs, writer: &mut W) -> Result<()> {
+    // standard input is equal to itself, reading it for both would block on one lock
+    if args.left == Path::new("-") && args.right == Path::new("-") {
+        return Ok(());
+    }
+    let mut left = open(&args.left)?;
+    let mut right = open(&args.right)?;
+    compare(args, &mut left, &mut right, writer)
+}
+
+fn open(path: &Path) -> Result<Box<dyn Read>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    let file = File::open(path).map_err(|source| CmpError::ReadFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(Box::new(file))
+}
+
+fn compare<W: Write>(
+    args: &CmpArgs,
+    left: &mut impl Read,
+    right: &mut impl Read,
+    writer: &mut W,
+) -> Result<()> {
+    let read_failed = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| CmpError::ReadFailed { path, source }
+    };
+    let mut left_block = vec![0; BLOCK_SIZE];
+    let mut right_block = vec![0; BLOCK_SIZE];
+    // bytes and lines before current block
+    let mut offset = 0;
+    let mut line = 1;
+    let mut differ = false;
+
+    loop {
+        cancel::check()?;
+        let left_len = read_full(left, &mut left_block).map_err(read_failed(&args.left))?;
+        let right_len = read_full(right, &mut right_block).map_err(read_failed(&args.right))?;
+        let len = left_len.min(right_len);
+        let (left_bytes, right_bytes) = (&left_block[..len], &right_block[..len]);
+
+        if left_bytes != right_bytes {
+            if args.silent {
+                return Err(CmpError::Differ);
+            }
+            if !args.list {
+                let i = first_difference(left_bytes, right_bytes);
+                writeln!(
+                    writer,
+                    "{} {} differ: byte {}, line {}",
+                    paths::native(&args.left),
+                    paths::native(&args.right),
+                    offset + i as u64 + 1,
+                    line + count_lines(&left_bytes[..i])
+                )?;
+                return Err(CmpError::Differ);
+            }
+
+            differ = true;
+            for (i, (l, r)) in left_bytes.iter().zip(right_bytes).enumerate() {
+                if l != r {
+                    writeln!(writer, "{} {:02x} {:02x}", offset + i as u64 + 1, l, r)?;
+                }
+            }
+        }
+        offset += len as u64;
+        line += count_lines(left_bytes);
+
+        if left_len != right_len {
+            if !args.silent {
+                let shorter = if left_len < right_len {
+                    &args.left
+                } else {
+                    &args.right
+                };
+                writer.flush()?;
+                // same message as `cmp`, a file that is a prefix of the other one differs too
+                eprintln!(
+                    "cmp: EOF on {} after byte {}",
+                    paths::native(shorter),
+                    offset
+                );
+            }
+            return Err(CmpError::Differ);
+        }
+        if len == 0 {
+            break;
+        }
+    }
+
+    if differ {
+        Err(CmpError::Differ)
+    } else {
+        Ok(())
+    }
+}
+
+fn first_difference(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right)
+        .position(|(l, r)| l != r)
+        .unwrap_or(left.len().min(right.len()))
+}
+
+fn count_lines(bytes: &[u8]) -> u64 {
+    bytes.iter().filter(|&&b| b == b'\n').count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare_bytes(args: &[&str], left: &[u8], right: &[u8]) -> (String, bool) {
+        let args = CmpArgs::parse_from(["cmp", "a", "b"].iter().chain(args));
+        let mut output = vec![];
+        let result = compare(&args, &mut &left[..], &mut &right[..], &mut output);
+        (String::from_utf8(output).unwrap(), result.is_ok())
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(compare_bytes(&[], b"same", b"same"), (String::new(), true));
+        assert_eq!(
+            compare_bytes(&[], b"ab\ncd", b"ab\nce"),
+            ("a b differ: byte 5, line 2\n".to_string(), false)
+        );
+        assert_eq!(
+            compare_bytes(&["-l"], b"abcd", b"xbcy"),
+            ("1 61 78\n4 64 79\n".to_string(), false)
+        );
+        assert_eq!(
+            compare_bytes(&["-s"], b"ab", b"abc"),
+            (String::new(), false)
+        );
+    }
+}
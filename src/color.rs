@@ -0,0 +1,48 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use clap::ValueEnum;
+
+/// Color policy of global `--color` option
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ColorWhen {
+    Always,
+    /// Only when output is a terminal, see `enabled_for`
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorWhen {
+    /// Whether output written to stdout is colored
+    pub fn enabled(self) -> bool {
+        self.enabled_for(io::stdout().is_terminal())
+    }
+
+    /// Whether output is colored, `auto` colors terminal output unless `NO_COLOR` is set and
+    /// `CLICOLOR_FORCE` colors any output
+    pub fn enabled_for(self, is_terminal: bool) -> bool {
+        match self {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto if env_flag("NO_COLOR") => false,
+            ColorWhen::Auto if env_flag("CLICOLOR_FORCE") => true,
+            ColorWhen::Auto => is_terminal,
+        }
+    }
+}
+
+/// Makes Windows console interpret ANSI color escapes of stdout instead of printing them, which
+/// cmd and older PowerShell hosts don't do by default. Nothing to do on other platforms.
+pub fn enable_terminal_colors() {
+    #[cfg(windows)]
+    {
+        // fails when stdout isn't a console, where `auto` doesn't color anyway
+        let _ = colored::control::set_virtual_terminal(true);
+    }
+}
+
+/// Variable set to a non-empty value, the convention of both `NO_COLOR` and `CLICOLOR_FORCE`
+fn env_flag(name: &str) -> bool {
+    env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0")
+}
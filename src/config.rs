@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// environment variable overriding default config file location
+pub const CONFIG_ENV: &str = "KF_CONFIG";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to read config file {}: {source}", .path.display())]
+    IoError { path: PathBuf, source: io::Error },
+
+    #[error("invalid config file {}: {source}", .path.display())]
+    ParseError {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+/// User config, read from `$KF_CONFIG` or `<config dir>/kf/config.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// named line ranges referenced as `@name`, e.g. `recent = "-200.."`
+    pub ranges: HashMap<String, String>,
+    /// default of global `--color`, e.g. `color = "never"`
+    pub color: Option<String>,
+    pub grep: GrepConfig,
+    pub view: ViewConfig,
+    pub hex: CommandConfig,
+    pub echo: CommandConfig,
+    pub plugins: PluginConfig,
+}
+
+/// `[<command>]` table with default arguments, e.g. `args = ["--group-size", "4"]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CommandConfig {
+    pub args: Vec<String>,
+}
+
+/// `[plugins]` table, external `kf-<name>` commands found on PATH
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// only plugins named here may run, e.g. `allow = ["fmt"]`, any plugin when missing
+    pub allow: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GrepConfig {
+    /// globs of names skipped in recursive search, e.g. `ignore = ["target", ".git"]`
+    pub ignore: Vec<String>,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ViewConfig {
+    /// default `--columns` of hex format
+    pub bytes_per_line: Option<usize>,
+    pub args: Vec<String>,
+}
+
+impl Config {
+    /// Loads config file, missing file gives default config
+    pub fn load() -> Result<Self, ConfigError> {
+        match config_path() {
+            Some(path) => Self::load_from(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Default arguments of subcommand, inserted before arguments given on command line so
+    /// that those override them
+    pub fn default_args(&self, command: &str) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(color) = &self.color
+            && matches!(command, "grep" | "view" | "hex" | "echo" | "replace")
+        {
+            args.push(format!("--color={}", color));
+        }
+
+        match command {
+            "grep" => {
+                args.extend(self.grep.ignore.iter().map(|glob| format!("--ignore={}", glob)));
+                args.extend(self.grep.args.iter().cloned());
+            }
+            "view" => {
+                if let Some(columns) = self.view.bytes_per_line {
+                    args.push(format!("--columns={}", columns));
+                }
+                args.extend(self.view.args.iter().cloned());
+            }
+            "hex" => args.extend(self.hex.args.iter().cloned()),
+            "echo" => args.extend(self.echo.args.iter().cloned()),
+            _ => {}
+        }
+        args
+    }
+
+    fn load_from(path: PathBuf) -> Result<Self, ConfigError> {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(ConfigError::IoError { path, source }),
+        };
+
+        toml::from_str(&content).map_err(|source| ConfigError::ParseError { path, source })
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::config_dir().map(|dir| dir.join("kf").join("config.toml")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranges() {
+        let config: Config = toml::from_str("[ranges]\nheader = \"..20\"\n").unwrap();
+        assert_eq!(config.ranges["header"], "..20");
+
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.ranges.is_empty());
+    }
+
+    #[test]
+    fn test_default_args() {
+        let config: Config = toml::from_str(
+            "color = \"never\"\n[grep]\nignore = [\"target\"]\nargs = [\"-i\"]\n\
+             [view]\nbytes_per_line = 8\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.default_args("grep"),
+            ["--color=never", "--ignore=target", "-i"]
+        );
+        assert_eq!(
+            config.default_args("view"),
+            ["--color=never", "--columns=8"]
+        );
+        assert_eq!(config.default_args("hex"), ["--color=never"]);
+        assert!(config.default_args("mangen").is_empty());
+    }
+}
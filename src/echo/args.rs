@@ -0,0 +1,125 @@
+use clap::{ArgAction, Parser};
+
+use super::style::TextStyle;
+use crate::color::ColorWhen;
+
+#[derive(Debug, Parser)]
+pub struct EchoArgs {
+    #[arg(
+        index = 1,
+        num_args=0..,
+        help = "Words to echo, piped standard input is copied to output when no words given"
+    )]
+    pub words: Vec<String>,
+
+    #[arg(short = 'n', help = "Do not print the trailing newline character")]
+    pub omit_newline: bool,
+
+    #[arg(
+        short = '0',
+        long,
+        conflicts_with = "omit_newline",
+        help = "Terminate output with NUL instead of newline, for 'xargs -0'"
+    )]
+    pub null: bool,
+
+    #[arg(
+        short,
+        long,
+        default_value = " ",
+        value_parser = unescape,
+        help = "Separator between words, escapes '\\0', '\\t', '\\n', '\\r' and '\\\\' are allowed"
+    )]
+    pub separator: String,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        help = "Output message N times, 0 repeats it until output is closed like 'yes'"
+    )]
+    pub repeat: u64,
+
+    #[arg(
+        long,
+        help = "Substitute '$VAR' and '${VAR}' with environment variables and '{}' with --arg values"
+    )]
+    pub expand: bool,
+
+    #[arg(
+        long = "arg",
+        value_name = "VALUE",
+        action = ArgAction::Append,
+        requires = "expand",
+        help = "Value for next '{}' placeholder of --expand, repeat for more placeholders"
+    )]
+    pub values: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "STYLE",
+        value_parser = clap::value_parser!(TextStyle),
+        help = "Style of echoed words, e.g. 'bold,red' or 'underline,on_blue'"
+    )]
+    pub style: Option<TextStyle>,
+
+    /// set from global `--color`
+    #[arg(skip)]
+    pub color: ColorWhen,
+
+    #[arg(long, help = "Write to standard error instead of standard output")]
+    pub stderr: bool,
+
+    #[arg(long, help = "Print parsed arguments to standard error before output")]
+    pub debug: bool,
+}
+
+impl EchoArgs {
+    /// Terminator of output, replaces trailing newline of copied standard input
+    pub fn ending(&self) -> &'static str {
+        if self.null {
+            "\0"
+        } else if self.omit_newline {
+            ""
+        } else {
+            "\n"
+        }
+    }
+}
+
+/// Replaces backslash escapes `\0`, `\t`, `\n`, `\r` and `\\` with characters they stand for
+fn unescape(value: &str) -> std::result::Result<String, String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('0') => '\0',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('\\') => '\\',
+            Some(other) => return Err(format!("unknown escape '\\{}'", other)),
+            None => return Err("trailing backslash".to_string()),
+        };
+        result.push(escaped);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(",").unwrap(), ",");
+        assert_eq!(unescape("\\0").unwrap(), "\0");
+        assert_eq!(unescape("a\\tb\\\\").unwrap(), "a\tb\\");
+        assert!(unescape("\\x").is_err());
+        assert!(unescape("\\").is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+use crate::exit::{self, ExitCode};
+
+#[derive(Debug, Error)]
+pub enum EchoError {
+    #[error("Echo command error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("missing '}}' of variable starting at position {0}")]
+    UnterminatedVariable(usize),
+
+    #[error("{placeholders} '{{}}' placeholders but only {values} --arg values")]
+    MissingValues { placeholders: usize, values: usize },
+}
+
+impl ExitCode for EchoError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            EchoError::IoError(_) => exit::IO,
+            EchoError::UnterminatedVariable(_) | EchoError::MissingValues { .. } => exit::USAGE,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, EchoError>;
@@ -0,0 +1,104 @@
+use super::error::{EchoError, Result};
+
+/// Substitutes `$VAR` and `${VAR}` with variables returned by `lookup`, unknown ones are empty
+/// like in shell, and each `{}` with next of `values`. `$$`, `{{` and `}}` stand for literal
+/// characters.
+pub fn expand<F>(template: &str, lookup: F, values: &[String]) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(template.len());
+    let mut placeholders = 0;
+    let mut rest = template;
+
+    while let Some(c) = rest.chars().next() {
+        let position = template.len() - rest.len();
+        match c {
+            '$' if rest.starts_with("$$") => {
+                result.push('$');
+                rest = &rest[2..];
+            }
+            '$' if rest.starts_with("${") => {
+                let end = rest.find('}').ok_or(EchoError::UnterminatedVariable(position))?;
+                result.push_str(&lookup(&rest[2..end]).unwrap_or_default());
+                rest = &rest[end + 1..];
+            }
+            '$' => {
+                let name_len = rest[1..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len() - 1);
+                if name_len == 0 {
+                    result.push('$');
+                } else {
+                    result.push_str(&lookup(&rest[1..=name_len]).unwrap_or_default());
+                }
+                rest = &rest[1 + name_len..];
+            }
+            '{' if rest.starts_with("{{") => {
+                result.push('{');
+                rest = &rest[2..];
+            }
+            '}' if rest.starts_with("}}") => {
+                result.push('}');
+                rest = &rest[2..];
+            }
+            '{' if rest.starts_with("{}") => {
+                if let Some(value) = values.get(placeholders) {
+                    result.push_str(value);
+                }
+                placeholders += 1;
+                rest = &rest[2..];
+            }
+            c => {
+                result.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if placeholders > values.len() {
+        return Err(EchoError::MissingValues {
+            placeholders,
+            values: values.len(),
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "USER").then(|| "kos".to_string())
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        assert_eq!(expand("hi $USER!", lookup, &[]).unwrap(), "hi kos!");
+        assert_eq!(expand("${USER}_x", lookup, &[]).unwrap(), "kos_x");
+        assert_eq!(expand("$UNSET.", lookup, &[]).unwrap(), ".");
+        assert_eq!(
+            expand("$$USER costs $5 $", lookup, &[]).unwrap(),
+            "$USER costs  $"
+        );
+        assert!(matches!(
+            expand("${USER", lookup, &[]),
+            Err(EchoError::UnterminatedVariable(0))
+        ));
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let values = ["a".to_string(), "b".to_string()];
+        assert_eq!(expand("{} and {}", lookup, &values).unwrap(), "a and b");
+        assert_eq!(expand("{{}} {}", lookup, &values).unwrap(), "{} a");
+        assert!(matches!(
+            expand("{}{}{}", lookup, &values),
+            Err(EchoError::MissingValues {
+                placeholders: 3,
+                values: 2
+            })
+        ));
+    }
+}
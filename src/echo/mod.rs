@@ -0,0 +1,177 @@
+use std::env;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+
+mod args;
+mod error;
+mod expand;
+mod style;
+
+use crate::cancel;
+pub use args::EchoArgs;
+pub use error::{EchoError, Result};
+
+/// Echoes to stdout, or stderr when chosen by arguments
+pub fn echo(args: EchoArgs) -> Result<()> {
+    if args.style.is_some() {
+        // colored checks stdout on its own, but output may go to stderr
+        colored::control::set_override(color_enabled(&args));
+    }
+
+    let target: Box<dyn Write> = if args.stderr {
+        Box::new(io::stderr().lock())
+    } else {
+        Box::new(io::stdout().lock())
+    };
+    let mut writer = BufWriter::new(target);
+    echo_to(&args, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Echoes words, or piped standard input when there are none, to given writer. `--stderr` is
+/// ignored and styles follow `colored` settings.
+pub fn echo_to<W: Write>(args: &EchoArgs, writer: &mut W) -> Result<()> {
+    if args.debug {
+        eprintln!("{:#?}", args);
+    }
+
+    if args.words.is_empty() && !io::stdin().is_terminal() {
+        write_input(io::stdin().lock(), args, writer)?;
+        Ok(())
+    } else {
+        write_words(args, writer)
+    }
+}
+
+fn write_words<W: Write>(args: &EchoArgs, writer: &mut W) -> Result<()> {
+    let mut message = args.words.join(&args.separator);
+    if args.expand {
+        message = expand::expand(&message, |name| env::var(name).ok(), &args.values)?;
+    }
+    if let Some(style) = &args.style {
+        message = style.apply(&message).to_string();
+    }
+    message.push_str(args.ending());
+    write_repeated(message.as_bytes(), args.repeat, writer)?;
+    Ok(())
+}
+
+/// Whether styles are applied, `auto` depends on where output is written to
+fn color_enabled(args: &EchoArgs) -> bool {
+    let is_terminal = if args.stderr {
+        io::stderr().is_terminal()
+    } else {
+        io::stdout().is_terminal()
+    };
+    args.color.enabled_for(is_terminal)
+}
+
+/// Copies input to output, its trailing newline is replaced by `-n` or `-0`. Input is streamed
+/// unless it's repeated, then it has to be kept in memory.
+fn write_input<R: BufRead, W: Write>(
+    mut reader: R,
+    args: &EchoArgs,
+    writer: &mut W,
+) -> io::Result<()> {
+    let ending = args.ending();
+    let replace_newline = ending != "\n";
+
+    if args.repeat != 1 {
+        let mut input = vec![];
+        reader.read_to_end(&mut input)?;
+        if replace_newline {
+            if input.ends_with(b"\n") {
+                input.pop();
+            }
+            input.extend_from_slice(ending.as_bytes());
+        }
+        return write_repeated(&input, args.repeat, writer);
+    }
+
+    // newline ending a chunk is held back until it's known not to be the last byte
+    let mut pending_newline = false;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        if pending_newline {
+            writer.write_all(b"\n")?;
+        }
+        let content = match buffer.strip_suffix(b"\n") {
+            Some(content) if replace_newline => content,
+            _ => buffer,
+        };
+        writer.write_all(content)?;
+        pending_newline = content.len() < buffer.len();
+        let len = buffer.len();
+        reader.consume(len);
+    }
+    if replace_newline {
+        writer.write_all(ending.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes message `count` times, forever when `count` is 0
+fn write_repeated<W: Write>(message: &[u8], count: u64, writer: &mut W) -> io::Result<()> {
+    // nothing would ever be written to notice a closed output
+    if message.is_empty() {
+        return Ok(());
+    }
+    if count == 0 {
+        loop {
+            writer.write_all(message)?;
+            cancel::check()?;
+        }
+    }
+    for _ in 0..count {
+        writer.write_all(message)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn echo_output(args: &[&str]) -> String {
+        let args = EchoArgs::parse_from(std::iter::once("echo").chain(args.iter().copied()));
+        let mut output = vec![];
+        write_words(&args, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_echo_words() {
+        assert_eq!(echo_output(&["a", "b"]), "a b\n");
+        assert_eq!(echo_output(&["-n", "a", "b"]), "a b");
+        assert_eq!(echo_output(&["-0", "a"]), "a\0");
+        assert_eq!(echo_output(&["-s", "\\t", "a", "b"]), "a\tb\n");
+        assert_eq!(echo_output(&[]), "\n");
+    }
+
+    #[test]
+    fn test_echo_repeat_and_expand() {
+        assert_eq!(echo_output(&["--repeat", "3", "y"]), "y\ny\ny\n");
+        assert_eq!(
+            echo_output(&["--expand", "{}={}", "--arg", "k", "--arg", "v"]),
+            "k=v\n"
+        );
+    }
+
+    #[test]
+    fn test_write_input() {
+        let args = EchoArgs::parse_from(["echo", "-0"]);
+        let mut output = vec![];
+        write_input(&b"a\nb\n"[..], &args, &mut output).unwrap();
+        assert_eq!(output, b"a\nb\0");
+
+        let args = EchoArgs::parse_from(["echo", "-n", "--repeat", "2"]);
+        let mut output = vec![];
+        write_input(&b"x\n"[..], &args, &mut output).unwrap();
+        assert_eq!(output, b"xx");
+    }
+}
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use colored::{Color, ColoredString, Colorize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Attribute {
+    Bold,
+    Dimmed,
+    Italic,
+    Underline,
+    Reversed,
+    Strikethrough,
+}
+
+/// Text style parsed from `--style` like `bold,red` or `underline,on_blue`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextStyle {
+    foreground: Option<Color>,
+    background: Option<Color>,
+    attributes: Vec<Attribute>,
+}
+
+impl FromStr for TextStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = TextStyle::default();
+
+        for item in s.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let attribute = match item {
+                "bold" => Attribute::Bold,
+                "dimmed" => Attribute::Dimmed,
+                "italic" => Attribute::Italic,
+                "underline" => Attribute::Underline,
+                "reversed" => Attribute::Reversed,
+                "strikethrough" => Attribute::Strikethrough,
+                _ => {
+                    let (name, background) = match item.strip_prefix("on_") {
+                        Some(name) => (name, true),
+                        None => (item, false),
+                    };
+                    let color =
+                        name.parse().map_err(|_| format!("unknown style or color '{}'", item))?;
+                    if background {
+                        style.background = Some(color);
+                    } else {
+                        style.foreground = Some(color);
+                    }
+                    continue;
+                }
+            };
+            style.attributes.push(attribute);
+        }
+
+        Ok(style)
+    }
+}
+
+impl TextStyle {
+    pub fn apply(&self, text: &str) -> ColoredString {
+        let mut styled = text.normal();
+        if let Some(color) = self.foreground {
+            styled = styled.color(color);
+        }
+        if let Some(color) = self.background {
+            styled = styled.on_color(color);
+        }
+        for attribute in &self.attributes {
+            styled = match attribute {
+                Attribute::Bold => styled.bold(),
+                Attribute::Dimmed => styled.dimmed(),
+                Attribute::Italic => styled.italic(),
+                Attribute::Underline => styled.underline(),
+                Attribute::Reversed => styled.reversed(),
+                Attribute::Strikethrough => styled.strikethrough(),
+            };
+        }
+        styled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            "bold, red,on_white".parse::<TextStyle>().unwrap(),
+            TextStyle {
+                foreground: Some(Color::Red),
+                background: Some(Color::White),
+                attributes: vec![Attribute::Bold],
+            }
+        );
+        assert_eq!("".parse::<TextStyle>().unwrap(), TextStyle::default());
+        assert!("blinking".parse::<TextStyle>().is_err());
+        assert!("on_".parse::<TextStyle>().is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Exit codes shared by all commands
+//!
+//! | code | meaning                                                  |
+//! |------|----------------------------------------------------------|
+//! | 0    | success                                                  |
+//! | 1    | nothing found or files differ, e.g. grep without matches |
+//! | 2    | wrong usage, invalid arguments or config file            |
+//! | 3    | failure to read or write files, or unusable file content |
+//! | 130  | interrupted by Ctrl-C, like shells report SIGINT         |
+
+use crate::cli::CliError;
+use crate::config::ConfigError;
+
+pub const SUCCESS: i32 = 0;
+pub const NOT_FOUND: i32 = 1;
+pub const USAGE: i32 = 2;
+pub const IO: i32 = 3;
+pub const INTERRUPTED: i32 = 130;
+
+/// Exit code of process failed with an error, one of constants of this module
+pub trait ExitCode {
+    fn exit_code(&self) -> i32;
+}
+
+impl ExitCode for ConfigError {
+    fn exit_code(&self) -> i32 {
+        USAGE
+    }
+}
+
+impl ExitCode for CliError {
+    fn exit_code(&self) -> i32 {
+        if self.is_interrupted() {
+            return INTERRUPTED;
+        }
+
+        match self {
+            // 0 for `--help` and `--version`, USAGE for real errors
+            CliError::Usage(e) => clap::Error::exit_code(e),
+            CliError::Grep(e) => e.exit_code(),
+            CliError::View(e) => e.exit_code(),
+            CliError::Echo(e) => e.exit_code(),
+            CliError::Hex(e) => e.exit_code(),
+            CliError::Cmp(e) => e.exit_code(),
+            CliError::Config(e) => e.exit_code(),
+            CliError::Output { .. } | CliError::ArgFile { .. } | CliError::Io(_) => IO,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use globset::Glob;
+use regex::Regex;
+
+use super::filter::{Amount, SIZE_UNITS, TIME_UNITS};
+
+#[derive(Debug, Parser)]
+pub struct FindArgs {
+    #[arg(
+        index = 1,
+        help = "Directories to search, current directory when not specified"
+    )]
+    pub paths: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = parse_glob,
+        help = "Only entries with matching file name, e.g. '*.rs'"
+    )]
+    pub name: Option<Glob>,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = parse_path_regex,
+        help = "Only entries whose whole path matches regex"
+    )]
+    pub regex: Option<Regex>,
+
+    #[arg(
+        short = 't',
+        long = "type",
+        value_name = "TYPE",
+        value_enum,
+        help = "Only entries of this type"
+    )]
+    pub entry_type: Option<EntryType>,
+
+    #[arg(
+        long,
+        value_name = "[+-]N[kMG]",
+        allow_hyphen_values = true,
+        value_parser = |value: &str| Amount::parse(value, SIZE_UNITS),
+        help = "Only files bigger (+), smaller (-) or exactly N bytes or units, rounded up"
+    )]
+    pub size: Option<Amount>,
+
+    #[arg(
+        long,
+        value_name = "[+-]N[smhd]",
+        allow_hyphen_values = true,
+        value_parser = |value: &str| Amount::parse(value, TIME_UNITS),
+        help = "Only entries modified more (+), less (-) or exactly N seconds or units ago, rounded down"
+    )]
+    pub mtime: Option<Amount>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = parse_glob,
+        help = "Skip files and directories with matching name, e.g. 'target' or '.git'"
+    )]
+    pub ignore: Vec<Glob>,
+
+    #[arg(
+        short = '0',
+        long,
+        help = "Terminate paths with NUL instead of newline, for 'xargs -0'"
+    )]
+    pub print0: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum EntryType {
+    /// Regular file
+    #[value(alias = "f")]
+    File,
+    /// Directory
+    #[value(alias = "d")]
+    Dir,
+    /// Symbolic link
+    #[value(alias = "l")]
+    Symlink,
+}
+
+fn parse_glob(value: &str) -> Result<Glob, String> {
+    Glob::new(value).map_err(|e| e.to_string())
+}
+
+/// Regex anchored at both ends, it has to match whole path like `find -regex`
+pub fn parse_path_regex(value: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{})$", value)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,181 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use globset::GlobMatcher;
+use regex::Regex;
+
+use super::args::{EntryType, FindArgs};
+
+/// unit suffixes of `--size`
+pub const SIZE_UNITS: &[(char, u64)] = &[('c', 1), ('k', 1 << 10), ('M', 1 << 20), ('G', 1 << 30)];
+
+/// unit suffixes of `--mtime`
+pub const TIME_UNITS: &[(char, u64)] = &[('s', 1), ('m', 60), ('h', 3600), ('d', 86400)];
+
+/// Comparison of `--size` and `--mtime` like `+10k`, `-2d` or `5M`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Amount {
+    MoreThan(u64, u64),
+    LessThan(u64, u64),
+    Exactly(u64, u64),
+}
+
+impl Amount {
+    /// Parses `[+-]N[unit]` into count of units and unit size, bare number counts first unit
+    pub fn parse(value: &str, units: &[(char, u64)]) -> Result<Amount, String> {
+        let (sign, rest) = match value.chars().next() {
+            Some(sign @ ('+' | '-')) => (Some(sign), &value[1..]),
+            _ => (None, value),
+        };
+        let (digits, unit) = match rest.chars().last() {
+            Some(suffix) if suffix.is_ascii_alphabetic() => {
+                let unit = units
+                    .iter()
+                    .find(|(name, _)| *name == suffix)
+                    .map(|(_, size)| *size)
+                    .ok_or_else(|| format!("unknown unit '{}'", suffix))?;
+                (&rest[..rest.len() - 1], unit)
+            }
+            _ => (rest, units[0].1),
+        };
+        let count = digits.parse().map_err(|_| format!("invalid number '{}'", digits))?;
+
+        Ok(match sign {
+            Some('+') => Amount::MoreThan(count, unit),
+            Some(_) => Amount::LessThan(count, unit),
+            None => Amount::Exactly(count, unit),
+        })
+    }
+
+    /// Compares amount measured in units, `round_up` decides how partial units count
+    fn matches(self, value: u64, round_up: bool) -> bool {
+        let in_units = |unit: u64| {
+            if round_up {
+                value.div_ceil(unit)
+            } else {
+                value / unit
+            }
+        };
+        match self {
+            Amount::MoreThan(count, unit) => in_units(unit) > count,
+            Amount::LessThan(count, unit) => in_units(unit) < count,
+            Amount::Exactly(count, unit) => in_units(unit) == count,
+        }
+    }
+}
+
+/// Tests of `kf find`, an entry is printed when it passes all of them
+pub struct EntryFilter<'a> {
+    name: Option<GlobMatcher>,
+    regex: Option<&'a Regex>,
+    entry_type: Option<EntryType>,
+    size: Option<Amount>,
+    mtime: Option<Amount>,
+    now: SystemTime,
+}
+
+impl<'a> EntryFilter<'a> {
+    pub fn from_args(args: &'a FindArgs) -> Self {
+        Self {
+            name: args.name.as_ref().map(|glob| glob.compile_matcher()),
+            regex: args.regex.as_ref(),
+            entry_type: args.entry_type,
+            size: args.size,
+            mtime: args.mtime,
+            now: SystemTime::now(),
+        }
+    }
+
+    pub fn matches(&self, path: &Path) -> io::Result<bool> {
+        if let Some(name) = &self.name
+            && !path.file_name().is_some_and(|file_name| name.is_match(file_name))
+        {
+            return Ok(false);
+        }
+        if let Some(regex) = self.regex
+            && !regex.is_match(&path.to_string_lossy())
+        {
+            return Ok(false);
+        }
+
+        // metadata is only read when needed
+        if self.entry_type.is_none() && self.size.is_none() && self.mtime.is_none() {
+            return Ok(true);
+        }
+        let link_metadata = fs::symlink_metadata(path)?;
+        if let Some(entry_type) = self.entry_type
+            && !is_type(entry_type, path, &link_metadata)
+        {
+            return Ok(false);
+        }
+        let metadata = if link_metadata.is_symlink() {
+            fs::metadata(path)?
+        } else {
+            link_metadata
+        };
+        if let Some(size) = self.size
+            && !(metadata.is_file() && size.matches(metadata.len(), true))
+        {
+            return Ok(false);
+        }
+        if let Some(mtime) = self.mtime {
+            let age = self.now.duration_since(metadata.modified()?).map_or(0, |age| age.as_secs());
+            if !mtime.matches(age, false) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Symlinks are also files or directories they point to, except for type `symlink`
+fn is_type(entry_type: EntryType, path: &Path, link_metadata: &Metadata) -> bool {
+    match entry_type {
+        EntryType::Symlink => link_metadata.is_symlink(),
+        EntryType::File => path.is_file(),
+        EntryType::Dir => path.is_dir(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::args::parse_path_regex;
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(
+            Amount::parse("+10k", SIZE_UNITS),
+            Ok(Amount::MoreThan(10, 1024))
+        );
+        assert_eq!(
+            Amount::parse("-2d", TIME_UNITS),
+            Ok(Amount::LessThan(2, 86400))
+        );
+        assert_eq!(Amount::parse("5", SIZE_UNITS), Ok(Amount::Exactly(5, 1)));
+        assert!(Amount::parse("5x", SIZE_UNITS).is_err());
+        assert!(Amount::parse("+", SIZE_UNITS).is_err());
+    }
+
+    #[test]
+    fn test_regex_matches_whole_path() {
+        let regex = parse_path_regex("bc").unwrap();
+        assert!(!regex.is_match("fx/sub/abc.rs"));
+        let regex = parse_path_regex(".*/a?bc\\.rs").unwrap();
+        assert!(regex.is_match("fx/sub/abc.rs"));
+    }
+
+    #[test]
+    fn test_amount_matches() {
+        // 1500 bytes are 2 KiB rounded up
+        assert!(Amount::MoreThan(1, 1024).matches(1500, true));
+        assert!(Amount::Exactly(2, 1024).matches(1500, true));
+        assert!(!Amount::LessThan(2, 1024).matches(1500, true));
+        // 36 hours are 1 day rounded down
+        assert!(Amount::Exactly(1, 86400).matches(36 * 3600, false));
+        assert!(Amount::LessThan(2, 86400).matches(36 * 3600, false));
+    }
+}
@@ -0,0 +1,64 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use globset::GlobSetBuilder;
+
+mod args;
+mod filter;
+
+pub use args::{EntryType, FindArgs};
+use filter::EntryFilter;
+
+use crate::cancel;
+use crate::grep::FilesFinder;
+use crate::paths;
+use crate::report;
+
+pub fn find(args: FindArgs) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    find_to(&args, &mut writer)?;
+    writer.flush()
+}
+
+/// Prints matching entries under paths, unreadable ones are reported and skipped
+pub fn find_to<W: Write>(args: &FindArgs, writer: &mut W) -> io::Result<()> {
+    let mut ignore = GlobSetBuilder::new();
+    for glob in &args.ignore {
+        ignore.add(glob.clone());
+    }
+    let ignore = ignore.build().map_err(io::Error::other)?;
+    let paths = if args.paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        args.paths.clone()
+    };
+
+    let filter = EntryFilter::from_args(args);
+    let terminator = if args.print0 { '\0' } else { '\n' };
+    let mut failed = 0;
+    for entry in FilesFinder::all_entries(&paths, &ignore).find_files() {
+        cancel::check()?;
+        let matched = entry.and_then(|path| {
+            let with_path =
+                |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+            let matched = filter.matches(&path).map_err(with_path)?;
+            Ok(matched.then_some(path))
+        });
+        match matched {
+            Ok(Some(path)) => write!(writer, "{}{}", paths::native(&path), terminator)?,
+            Ok(None) => {}
+            Err(e) => {
+                report::file_error("find", e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "unreadable entries skipped: {}",
+            failed
+        )));
+    }
+    Ok(())
+}
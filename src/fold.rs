@@ -0,0 +1,210 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use clap::builder::TypedValueParser;
+
+use crate::report;
+
+#[derive(Debug, Parser)]
+pub struct FoldArgs {
+    #[arg(
+        index = 1,
+        help = "Files to wrap lines of, standard input when not specified or file is '-'"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'w',
+        long,
+        value_name = "N",
+        default_value_t = 80,
+        value_parser = clap::value_parser!(u32).range(1..).map(|width| width as usize),
+        help = "Maximum line width"
+    )]
+    pub width: usize,
+
+    #[arg(short = 'b', long, help = "Count bytes instead of characters")]
+    pub bytes: bool,
+
+    #[arg(
+        short = 's',
+        long,
+        help = "Break lines after last space within width, long words are still cut"
+    )]
+    pub spaces: bool,
+
+    #[arg(
+        short = 'p',
+        long,
+        conflicts_with_all = ["bytes", "spaces"],
+        help = "Join lines of paragraphs separated by blank lines and wrap them at words, like 'fmt'"
+    )]
+    pub reflow: bool,
+}
+
+/// Wraps long lines of files to standard output, like `fold` or `fmt` with `--reflow`
+pub fn fold(args: FoldArgs) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    fold_to(&args, &mut writer)?;
+    writer.flush()
+}
+
+pub fn fold_to<W: Write>(args: &FoldArgs, writer: &mut W) -> io::Result<()> {
+    if args.file_paths.is_empty() {
+        return fold_reader(args, &mut io::stdin().lock(), writer);
+    }
+    // like `kf view`, an unreadable file is reported and the rest are still folded
+    let mut failed = 0;
+    for file_path in &args.file_paths {
+        let result = if file_path == Path::new("-") {
+            fold_reader(args, &mut io::stdin().lock(), writer)
+        } else {
+            File::open(file_path)
+                .and_then(|file| fold_reader(args, &mut BufReader::new(file), writer))
+        };
+        match result {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Err(e),
+            Err(e) => {
+                report::file_error("fold", format_args!("{}: {}", file_path.display(), e));
+                failed += 1;
+            }
+            Ok(()) => {}
+        }
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "unreadable files skipped: {}",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+fn fold_reader<R: BufRead, W: Write>(
+    args: &FoldArgs,
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut paragraph = vec![];
+    let mut line = vec![];
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        let (content, ending) = split_line_ending(&line);
+        if !args.reflow {
+            let segments = fold_line(content, args.width, args.bytes, args.spaces);
+            writer.write_all(&segments.join(&b'\n'))?;
+            writer.write_all(ending)?;
+        } else if content.iter().all(u8::is_ascii_whitespace) {
+            write_paragraph(&paragraph, args.width, writer)?;
+            paragraph.clear();
+            writer.write_all(content)?;
+            writer.write_all(ending)?;
+        } else {
+            paragraph.push(String::from_utf8_lossy(content).into_owned());
+        }
+        line.clear();
+    }
+
+    write_paragraph(&paragraph, args.width, writer)
+}
+
+/// Splits line without terminator into segments at most `width` bytes or characters long
+fn fold_line(line: &[u8], width: usize, count_bytes: bool, at_spaces: bool) -> Vec<&[u8]> {
+    // byte offsets where a unit ends, invalid UTF-8 is counted by bytes
+    let unit_ends: Vec<usize> = match std::str::from_utf8(line) {
+        Ok(text) if !count_bytes => text.char_indices().map(|(i, c)| i + c.len_utf8()).collect(),
+        _ => (1..=line.len()).collect(),
+    };
+
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut units = 0;
+    while unit_ends.len() - units > width {
+        let mut end = unit_ends[units + width - 1];
+        if at_spaces && let Some(space) = line[start..end].iter().rposition(|&b| b == b' ') {
+            end = start + space + 1;
+        }
+        segments.push(&line[start..end]);
+        units += unit_ends[units..].iter().take_while(|&&e| e <= end).count();
+        start = end;
+    }
+    segments.push(&line[start..]);
+    segments
+}
+
+/// Wraps words of paragraph lines greedily, lines keep indentation of its first line
+fn write_paragraph<W: Write>(lines: &[String], width: usize, writer: &mut W) -> io::Result<()> {
+    let Some(first) = lines.first() else {
+        return Ok(());
+    };
+    let indent = &first[..first.len() - first.trim_start().len()];
+
+    let mut current = String::new();
+    for word in lines.iter().flat_map(|line| line.split_whitespace()) {
+        let len = current.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            writeln!(writer, "{}", current)?;
+            current.clear();
+        }
+        if current.is_empty() {
+            current.push_str(indent);
+        } else {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    writeln!(writer, "{}", current)
+}
+
+fn split_line_ending(line: &[u8]) -> (&[u8], &[u8]) {
+    if let Some(content) = line.strip_suffix(b"\r\n") {
+        (content, b"\r\n")
+    } else if let Some(content) = line.strip_suffix(b"\n") {
+        (content, b"\n")
+    } else {
+        (line, b"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold_text(line: &str, width: usize, count_bytes: bool, at_spaces: bool) -> Vec<String> {
+        fold_line(line.as_bytes(), width, count_bytes, at_spaces)
+            .into_iter()
+            .map(|segment| String::from_utf8_lossy(segment).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_fold_line() {
+        assert_eq!(fold_text("abcdefg", 3, false, false), ["abc", "def", "g"]);
+        assert_eq!(fold_text("abc", 3, false, false), ["abc"]);
+        assert_eq!(
+            fold_text("日本語テキスト", 3, false, false),
+            ["日本語", "テキス", "ト"]
+        );
+        assert_eq!(fold_text("日本", 3, true, false).len(), 2);
+        assert_eq!(
+            fold_text("ab cd efgh", 5, false, true),
+            ["ab ", "cd ", "efgh"]
+        );
+        assert_eq!(fold_text("abcdefgh", 5, false, true), ["abcde", "fgh"]);
+    }
+
+    #[test]
+    fn test_reflow() {
+        let args = FoldArgs::parse_from(["fold", "--reflow", "-w", "10"]);
+        let mut output = vec![];
+        let input = "  aaa bbb\n  ccc ddd eee\n\nfff\n";
+        fold_reader(&args, &mut input.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "  aaa bbb\n  ccc ddd\n  eee\n\nfff\n"
+        );
+    }
+}
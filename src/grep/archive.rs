@@ -0,0 +1,82 @@
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use super::matcher::{FileMatches, MatchesFinder};
+
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "zip" | "jar" => Some(ArchiveKind::Zip),
+            "tar" => Some(ArchiveKind::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// Searches every regular file entry inside archive, entries are named `archive.zip::inner/path`
+pub fn find_matches_from_archive(
+    finder: &MatchesFinder,
+    archive_path: &Path,
+    kind: ArchiveKind,
+) -> io::Result<Vec<FileMatches>> {
+    let file = File::open(archive_path)?;
+    match kind {
+        ArchiveKind::Zip => find_matches_from_zip(finder, archive_path, file),
+        ArchiveKind::Tar => find_matches_from_tar(finder, archive_path, file),
+    }
+}
+
+fn entry_path(archive_path: &Path, entry_name: &str) -> PathBuf {
+    PathBuf::from(format!("{}::{}", archive_path.display(), entry_name))
+}
+
+fn find_matches_from_zip(
+    finder: &MatchesFinder,
+    archive_path: &Path,
+    file: File,
+) -> io::Result<Vec<FileMatches>> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+    let mut results = vec![];
+
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+
+        let name = entry_path(archive_path, entry.name());
+        results.push(finder.find_matches_from_named_reader(name, BufReader::new(entry))?);
+    }
+
+    Ok(results)
+}
+
+fn find_matches_from_tar(
+    finder: &MatchesFinder,
+    archive_path: &Path,
+    file: File,
+) -> io::Result<Vec<FileMatches>> {
+    let mut archive = tar::Archive::new(BufReader::new(file));
+    let mut results = vec![];
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let name = entry_path(archive_path, &entry.path()?.to_string_lossy());
+        results.push(finder.find_matches_from_named_reader(name, BufReader::new(entry))?);
+    }
+
+    Ok(results)
+}
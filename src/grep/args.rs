@@ -1,19 +1,42 @@
-use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 
 use clap::builder::PossibleValuesParser;
 use clap::{ArgAction, Args, FromArgMatches};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use regex::{Regex, RegexBuilder};
 
+use crate::color::ColorWhen;
+use crate::view::RangeSpec;
+
+/// how file paths are printed, as typed by user when not specified
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathStyle {
+    Relative,
+    Absolute,
+}
+
 #[derive(Debug)]
 pub struct GrepArgs {
     pub pattern: Regex,
     pub files: Vec<PathBuf>,
     pub recursive: bool,
+    /// names of files and directories skipped during recursive search
+    pub ignore: GlobSet,
+    pub search_archives: bool,
     pub count: bool,
+    pub include_zero: bool,
+    pub only_matching: bool,
+    pub count_unique: bool,
     pub invert_match: bool,
     pub ignore_case: bool,
-    pub color: bool,
+    /// set from global `--color`
+    pub color: ColorWhen,
+    pub path_style: Option<PathStyle>,
+    pub trim: bool,
+    pub max_columns: Option<usize>,
+    pub lines: RangeSpec,
+    pub progress: bool,
+    pub read_devices: bool,
 }
 
 impl Args for GrepArgs {
@@ -41,6 +64,19 @@ impl Args for GrepArgs {
                     .action(ArgAction::SetTrue)
                     .help("Recursively search files in directory")
             )
+            .arg(
+                clap::Arg::new("ignore")
+                    .long("ignore")
+                    .value_name("GLOB")
+                    .action(ArgAction::Append)
+                    .help("Skip files and directories with matching name in recursive search, e.g. 'target' or '*.log'")
+            )
+            .arg(
+                clap::Arg::new("search_archives")
+                    .long("search-archives")
+                    .action(ArgAction::SetTrue)
+                    .help("Search entries of zip and tar archives")
+            )
             .arg(
                 clap::Arg::new("count")
                     .short('c')
@@ -48,6 +84,27 @@ impl Args for GrepArgs {
                     .action(ArgAction::SetTrue)
                     .help("Count occurrences")
             )
+            .arg(
+                clap::Arg::new("include_zero")
+                    .long("include-zero")
+                    .action(ArgAction::SetTrue)
+                    .requires("count")
+                    .help("Also list files without matches when counting")
+            )
+            .arg(
+                clap::Arg::new("only_matching")
+                    .short('o')
+                    .long("only-matching")
+                    .action(ArgAction::SetTrue)
+                    .help("Print only the matched parts of lines")
+            )
+            .arg(
+                clap::Arg::new("count_unique")
+                    .long("count-unique")
+                    .action(ArgAction::SetTrue)
+                    .requires("only_matching")
+                    .help("Print each distinct matched string with its occurrence count")
+            )
             .arg(
                 clap::Arg::new("invert_match")
                     .short('v')
@@ -63,14 +120,49 @@ impl Args for GrepArgs {
                     .help("Case insensitive pattern match")
             )
             .arg(
-                clap::Arg::new("color")
-                    .long("color")
-                    .value_name("WHEN")
-                    .num_args(0..=1)
-                    .default_missing_value("always")
-                    .default_value("auto")
-                    .value_parser(PossibleValuesParser::new(["always", "auto", "never"]))
-                    .help("Use markers to highlight the matching strings")
+                clap::Arg::new("path_style")
+                    .long("path-style")
+                    .value_name("STYLE")
+                    .value_parser(PossibleValuesParser::new(["relative", "absolute"]))
+                    .help("Print file paths relative to current directory or as absolute paths")
+            )
+            .arg(
+                clap::Arg::new("trim")
+                    .long("trim")
+                    .action(ArgAction::SetTrue)
+                    .help("Trim leading and trailing whitespace of output lines")
+            )
+            .arg(
+                clap::Arg::new("max_columns")
+                    .short('M')
+                    .long("max-columns")
+                    .value_name("NUM")
+                    .value_parser(clap::value_parser!(usize))
+                    .help("Truncate matched lines longer than NUM characters")
+            )
+            .arg(
+                clap::Arg::new("lines")
+                    .long("lines")
+                    .value_name("RANGE")
+                    .allow_hyphen_values(true)
+                    .default_value("-")
+                    .value_parser(clap::value_parser!(RangeSpec))
+                    .help("Only search lines inside range, e.g., '100..200' or '-50..'")
+            )
+            .arg(
+                clap::Arg::new("progress")
+                    .long("progress")
+                    .action(ArgAction::SetTrue)
+                    .help("Show search progress on standard error")
+            )
+            .arg(
+                clap::Arg::new("devices")
+                    .short('D')
+                    .long("devices")
+                    .value_name("ACTION")
+                    .default_value("skip")
+                    .value_parser(PossibleValuesParser::new(["read", "skip"]))
+                    .help("How to handle FIFOs, sockets and device files")
             )
     }
 
@@ -89,7 +181,7 @@ impl FromArgMatches for GrepArgs {
         })?;
         let ignore_case = matches.get_flag("ignore_case");
 
-        let mut builder = RegexBuilder::new(&pattern);
+        let mut builder = RegexBuilder::new(pattern);
         builder.case_insensitive(ignore_case);
         let pattern = builder.build().map_err(|e| {
             clap::Error::raw(
@@ -104,27 +196,51 @@ impl FromArgMatches for GrepArgs {
             .unwrap_or_default();
 
         let recursive = matches.get_flag("recursive");
+        let ignore = build_ignore(matches.get_many::<String>("ignore").into_iter().flatten())?;
+        let search_archives = matches.get_flag("search_archives");
         let count = matches.get_flag("count");
+        let include_zero = matches.get_flag("include_zero");
+        let only_matching = matches.get_flag("only_matching");
+        let count_unique = matches.get_flag("count_unique");
         let invert_match = matches.get_flag("invert_match");
-        let color = matches
-            .get_one::<String>("color")
-            .expect("Color option should have a default value");
-        let color = match color.as_str() {
-            "always" => true,
-            "never" => false,
-            "auto" => io::stdout().is_terminal(),
-            _ => unreachable!("color value parser ensures this doesn't happen"),
-        };
+
+        let path_style =
+            matches.get_one::<String>("path_style").map(|style| match style.as_str() {
+                "relative" => PathStyle::Relative,
+                "absolute" => PathStyle::Absolute,
+                _ => unreachable!("path style value parser ensures this doesn't happen"),
+            });
+        let trim = matches.get_flag("trim");
+        let max_columns = matches.get_one::<usize>("max_columns").copied();
+        let lines = matches
+            .get_one::<RangeSpec>("lines")
+            .cloned()
+            .expect("Lines option should have a default value");
+
+        let progress = matches.get_flag("progress");
+        let read_devices =
+            matches.get_one::<String>("devices").is_some_and(|action| action == "read");
 
         // 步骤4: 创建完整的 GrepArgs
         Ok(GrepArgs {
             pattern,
             files,
             recursive,
+            ignore,
+            search_archives,
             count,
+            include_zero,
+            only_matching,
+            count_unique,
             invert_match,
             ignore_case,
-            color,
+            color: ColorWhen::default(),
+            path_style,
+            trim,
+            max_columns,
+            lines,
+            progress,
+            read_devices,
         })
     }
 
@@ -135,3 +251,19 @@ impl FromArgMatches for GrepArgs {
         Ok(())
     }
 }
+
+fn build_ignore<'a>(globs: impl Iterator<Item = &'a String>) -> Result<GlobSet, clap::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = Glob::new(glob).map_err(|e| {
+            clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                format!("Invalid ignore glob '{}': {}", glob, e),
+            )
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| clap::Error::raw(clap::error::ErrorKind::InvalidValue, e))
+}
@@ -0,0 +1,68 @@
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+
+/// byte range `[start, end)` of a chunk, always starting at the beginning of a line
+pub type Chunk = (u64, u64);
+
+/// Splits `size` bytes of input into at most `count` chunks aligned to line boundaries.
+pub fn split_line_chunks<R: Read + Seek>(
+    reader: &mut R,
+    size: u64,
+    count: usize,
+) -> io::Result<Vec<Chunk>> {
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut buffer = vec![];
+
+    for i in 1..count as u64 {
+        let approx = size * i / count as u64;
+        if approx <= start {
+            continue;
+        }
+
+        // move boundary forward to the byte after next line break
+        reader.seek(SeekFrom::Start(approx))?;
+        buffer.clear();
+        let skipped = BufReader::new(&mut *reader).read_until(b'\n', &mut buffer)?;
+        let end = approx + skipped as u64;
+        if end >= size {
+            break;
+        }
+
+        chunks.push((start, end));
+        start = end;
+    }
+
+    if start < size {
+        chunks.push((start, size));
+    }
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_chunks_aligned_to_lines() {
+        let text = b"aaaa\nbb\ncccccc\nd\neeee\n";
+        let mut reader = Cursor::new(text);
+        let chunks = split_line_chunks(&mut reader, text.len() as u64, 3).unwrap();
+
+        assert_eq!(chunks, vec![(0, 8), (8, 15), (15, 22)]);
+        for (start, _) in &chunks[1..] {
+            assert_eq!(text[*start as usize - 1], b'\n');
+        }
+    }
+
+    #[test]
+    fn test_single_long_line() {
+        let text = b"aaaaaaaaaaaaaaaa";
+        let mut reader = Cursor::new(text);
+        let chunks = split_line_chunks(&mut reader, text.len() as u64, 4).unwrap();
+
+        assert_eq!(chunks, vec![(0, 16)]);
+    }
+}
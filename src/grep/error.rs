@@ -2,6 +2,8 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::exit::{self, ExitCode};
+
 #[derive(Error, Debug)]
 pub enum GrepError {
     #[error("Invalid pattern: {0}")]
@@ -14,4 +16,15 @@ pub enum GrepError {
     NoMatches,
 }
 
+impl ExitCode for GrepError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            GrepError::InvalidPattern(_) => exit::USAGE,
+            GrepError::IoError(_) => exit::IO,
+            // grep convention
+            GrepError::NoMatches => exit::NOT_FOUND,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, GrepError>;
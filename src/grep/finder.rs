@@ -1,13 +1,24 @@
 use std::{
+    collections::{HashSet, VecDeque},
     fs, io,
     path::{Path, PathBuf},
+    slice,
 };
 
+use globset::GlobSet;
+
 use super::args::GrepArgs;
 
+#[derive(Clone, Copy)]
 pub struct FilesFinder<'a> {
     files: &'a [PathBuf],
     recursive: bool,
+    ignore: &'a GlobSet,
+    read_devices: bool,
+    /// directories are listed before their entries
+    include_dirs: bool,
+    /// symlinks are searched as their targets, otherwise listed as entries themselves
+    follow_links: bool,
 }
 
 impl<'a> FilesFinder<'a> {
@@ -15,63 +26,212 @@ impl<'a> FilesFinder<'a> {
         Self {
             files: &args.files,
             recursive: args.recursive,
+            ignore: &args.ignore,
+            read_devices: args.read_devices,
+            include_dirs: false,
+            follow_links: true,
+        }
+    }
+
+    /// Finder of regular files, directories are only searched when `recursive`
+    pub fn new(files: &'a [PathBuf], recursive: bool, ignore: &'a GlobSet) -> Self {
+        Self {
+            files,
+            recursive,
+            ignore,
+            read_devices: false,
+            include_dirs: false,
+            follow_links: true,
+        }
+    }
+
+    /// Finder of every entry under paths including directories and special files, like `find`,
+    /// symlinks are listed but not followed
+    pub fn all_entries(files: &'a [PathBuf], ignore: &'a GlobSet) -> Self {
+        Self {
+            files,
+            recursive: true,
+            ignore,
+            read_devices: true,
+            include_dirs: true,
+            follow_links: false,
         }
     }
 
-    // TODO: use iterator to avoid collecting all files at once
-    pub fn find_files(&self) -> Vec<std::io::Result<PathBuf>> {
-        self.files.iter().flat_map(|path| self.find_files_at_path(path)).collect()
+    /// Walks paths lazily, files are yielded while directories are still being read
+    pub fn find_files(&self) -> FilesWalk<'a> {
+        FilesWalk {
+            finder: *self,
+            operands: self.files.iter(),
+            dirs: vec![],
+            pending: VecDeque::new(),
+            visited_dirs: HashSet::new(),
+            seen: HashSet::new(),
+        }
     }
+}
+
+/// Iterator of `FilesFinder::find_files`, directories are walked depth first in order of their
+/// entries
+pub struct FilesWalk<'a> {
+    finder: FilesFinder<'a>,
+    operands: slice::Iter<'a, PathBuf>,
+    /// directories being read, innermost last
+    dirs: Vec<(PathBuf, fs::ReadDir)>,
+    /// results to yield before reading on
+    pending: VecDeque<io::Result<PathBuf>>,
+    visited_dirs: HashSet<FileId>,
+    /// files yielded already, when links are followed
+    seen: HashSet<FileId>,
+}
+
+impl Iterator for FilesWalk<'_> {
+    type Item = io::Result<PathBuf>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.pending.pop_front() {
+                if self.is_new(&result) {
+                    return Some(result);
+                }
+                continue;
+            }
 
-    pub fn find_files_at_path(&self, path: &Path) -> Vec<std::io::Result<PathBuf>> {
-        let mut result = vec![];
-        let metadata = fs::metadata(path);
+            if let Some((dir_path, entries)) = self.dirs.last_mut() {
+                match entries.next() {
+                    Some(Ok(entry)) => self.visit_entry(&entry),
+                    Some(Err(e)) => {
+                        let error = with_path(dir_path, e);
+                        self.pending.push_back(Err(error));
+                    }
+                    None => {
+                        self.dirs.pop();
+                    }
+                }
+                continue;
+            }
+
+            let path = self.operands.next()?;
+            self.visit_operand(path);
+        }
+    }
+}
+
+impl FilesWalk<'_> {
+    /// Same file may be reached through multiple paths or symlinks, it's searched only once.
+    /// Without following links, a link and its target are two entries.
+    fn is_new(&mut self, result: &io::Result<PathBuf>) -> bool {
+        match result {
+            Ok(path) if self.finder.follow_links => {
+                file_identity(path).map_or(true, |id| self.seen.insert(id))
+            }
+            _ => true,
+        }
+    }
+
+    fn visit_operand(&mut self, path: &Path) {
+        let finder = self.finder;
+        let metadata = if finder.follow_links {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
 
         match metadata {
             Ok(f) => {
                 if f.is_file() {
-                    result.push(Ok(path.to_path_buf()));
+                    self.pending.push_back(Ok(path.to_path_buf()));
                 } else if f.is_dir() {
-                    if self.recursive {
-                        match self.find_files_in_dir(&path) {
-                            Err(e) => result.push(Err(e)),
-                            Ok(sub_files) => {
-                                result.extend(sub_files.into_iter().map(Ok));
-                            }
-                        }
+                    if finder.include_dirs {
+                        self.pending.push_back(Ok(path.to_path_buf()));
+                    }
+                    if finder.recursive {
+                        self.open_dir(path);
                     } else {
-                        result.push(Err(io::Error::new(
-                            io::ErrorKind::Other,
-                            format!(
-                                "{} is a directory, use -r to search recursively",
-                                path.display()
-                            ),
-                        )));
+                        self.pending.push_back(Err(io::Error::other(format!(
+                            "{} is a directory, use -r to search recursively",
+                            path.display()
+                        ))));
                     }
+                } else if finder.read_devices {
+                    self.pending.push_back(Ok(path.to_path_buf()));
+                } else {
+                    // fifo, socket or device file may block forever on read
+                    self.pending.push_back(Err(io::Error::other(format!(
+                        "{} is not a regular file, use -D read to read it",
+                        path.display()
+                    ))));
                 }
             }
-            Err(e) => {
-                result.push(Err(e));
-            }
+            Err(e) => self.pending.push_back(Err(with_path(path, e))),
         }
-
-        result
     }
 
-    fn find_files_in_dir<P: AsRef<Path>>(&self, dir_path: &P) -> io::Result<Vec<PathBuf>> {
-        let mut files = vec![];
-
-        for entry in fs::read_dir(dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                files.push(path);
-            } else if path.is_dir() && self.recursive {
-                let mut nested_files = self.find_files_in_dir(&path)?;
-                files.append(&mut nested_files);
+    fn visit_entry(&mut self, entry: &fs::DirEntry) {
+        let finder = self.finder;
+        let path = entry.path();
+        if finder.ignore.is_match(entry.file_name()) {
+            log::debug!("skipped {}, matches --ignore", path.display());
+            return;
+        }
+        let (is_file, is_dir) = if finder.follow_links {
+            (path.is_file(), path.is_dir())
+        } else {
+            // file type of entry itself, a symlink is neither file nor directory
+            entry.file_type().map_or((false, false), |t| (t.is_file(), t.is_dir()))
+        };
+        if is_file {
+            self.pending.push_back(Ok(path));
+        } else if is_dir && finder.recursive {
+            if finder.include_dirs {
+                self.pending.push_back(Ok(path.clone()));
             }
+            self.open_dir(&path);
+        } else if finder.read_devices && fs::symlink_metadata(&path).is_ok() {
+            self.pending.push_back(Ok(path));
+        } else {
+            log::debug!("skipped {}, not a regular file", path.display());
         }
+    }
 
-        Ok(files)
+    /// Starts reading directory, an unreadable one is reported and skipped, so that the rest of
+    /// the tree is still searched
+    fn open_dir(&mut self, dir_path: &Path) {
+        // a symlink to an ancestor directory would be followed forever
+        if self.finder.follow_links
+            && let Ok(id) = file_identity(dir_path)
+            && !self.visited_dirs.insert(id)
+        {
+            log::debug!("skipped {}, directory already searched", dir_path.display());
+            return;
+        }
+
+        match fs::read_dir(dir_path) {
+            Ok(entries) => self.dirs.push((dir_path.to_path_buf(), entries)),
+            Err(e) => self.pending.push_back(Err(with_path(dir_path, e))),
+        }
     }
 }
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+#[cfg(unix)]
+type FileId = (u64, u64);
+
+#[cfg(not(unix))]
+type FileId = PathBuf;
+
+#[cfg(unix)]
+fn file_identity(path: &Path) -> io::Result<FileId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(path: &Path) -> io::Result<FileId> {
+    fs::canonicalize(path)
+}
@@ -1,26 +1,41 @@
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+    thread,
 };
 
 use regex::Regex;
 
 use super::args::GrepArgs;
+use super::chunk::{self, Chunk};
+use crate::view::{RangeCount, RangePos, RangeSpec};
 
 #[derive(Debug, Clone)]
 pub struct LineMatch {
     pub line: String,
     pub line_number: usize,
+    /// byte ranges of pattern occurrences in line, empty for inverted match
+    pub spans: Vec<Range<usize>>,
+}
+
+/// files at least this large are searched in parallel chunks
+const PARALLEL_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// matches of a single chunk, line numbers are relative to chunk start
+struct ChunkMatches {
+    matches: Vec<LineMatch>,
+    line_count: usize,
 }
 
 #[derive(Debug)]
-pub struct FileMatches<'a> {
-    pub file_path: &'a Path,
+pub struct FileMatches {
+    pub file_path: PathBuf,
     pub matches: Vec<LineMatch>,
 }
 
-impl FileMatches<'_> {
+impl FileMatches {
     pub fn is_empty(&self) -> bool {
         self.matches.is_empty()
     }
@@ -33,6 +48,7 @@ impl FileMatches<'_> {
 pub struct MatchesFinder<'a> {
     pattern: &'a Regex,
     invert_match: bool,
+    lines: &'a RangeSpec,
 }
 
 impl<'a> MatchesFinder<'a> {
@@ -40,35 +56,130 @@ impl<'a> MatchesFinder<'a> {
         MatchesFinder {
             pattern: &args.pattern,
             invert_match: args.invert_match,
+            lines: &args.lines,
         }
     }
 
-    pub fn find_matches_from_file<'b>(&self, file: &'b Path) -> io::Result<FileMatches<'b>> {
-        let reader = BufReader::new(File::open(file)?);
-        let matches = self.find_matches_from_reader(reader)?;
+    pub fn find_matches_from_file(&self, file: &Path) -> io::Result<FileMatches> {
+        let size = fs::metadata(file)?.len();
+        let threads = thread::available_parallelism().map_or(1, usize::from);
+
+        let matches = if size >= PARALLEL_THRESHOLD && threads > 1 {
+            self.find_matches_in_chunks(file, size, threads)?
+        } else {
+            let reader = BufReader::new(File::open(file)?);
+            self.find_matches_from_reader(reader)?
+        };
 
         Ok(FileMatches {
-            file_path: file,
+            file_path: file.to_path_buf(),
             matches,
         })
     }
 
-    pub fn find_matches_from_stdin<R: BufRead>(&self, reader: R) -> io::Result<FileMatches<'_>> {
+    pub fn find_matches_from_stdin<R: BufRead>(&self, reader: R) -> io::Result<FileMatches> {
+        self.find_matches_from_named_reader(PathBuf::from("stdin"), reader)
+    }
+
+    /// searches content that's not a plain file, like stdin or archive entries
+    pub fn find_matches_from_named_reader<R: BufRead>(
+        &self,
+        name: PathBuf,
+        reader: R,
+    ) -> io::Result<FileMatches> {
         Ok(FileMatches {
-            file_path: Path::new("stdin"),
+            file_path: name,
             matches: self.find_matches_from_reader(reader)?,
         })
     }
 
+    fn find_matches_in_chunks(
+        &self,
+        file: &Path,
+        size: u64,
+        threads: usize,
+    ) -> io::Result<Vec<LineMatch>> {
+        let chunks = chunk::split_line_chunks(&mut File::open(file)?, size, threads)?;
+
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = chunks
+                .iter()
+                .map(|chunk| scope.spawn(move || self.find_matches_in_chunk(file, *chunk)))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("chunk search thread panicked"))
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+
+        // range filtering needs global line numbers, so it's applied after merging
+        let total: usize = results.iter().map(|result| result.line_count).sum();
+        let ranges = self.lines.normalize(total as RangeCount);
+
+        let mut matches = vec![];
+        let mut line_offset = 0;
+        for result in results {
+            matches.extend(
+                result
+                    .matches
+                    .into_iter()
+                    .map(|line_match| LineMatch {
+                        line_number: line_match.line_number + line_offset,
+                        ..line_match
+                    })
+                    .filter(|line_match| ranges.contains(line_match.line_number as RangePos)),
+            );
+            line_offset += result.line_count;
+        }
+
+        Ok(matches)
+    }
+
+    fn find_matches_in_chunk(&self, file: &Path, (start, end): Chunk) -> io::Result<ChunkMatches> {
+        let mut f = File::open(file)?;
+        f.seek(SeekFrom::Start(start))?;
+        let reader = BufReader::new(f.take(end - start));
+
+        let mut matches = vec![];
+        let mut line_count = 0;
+        for line in lossy_lines(reader) {
+            let line = line?;
+            line_count += 1;
+            if self.is_match(&line) {
+                matches.push(self.line_match(line, line_count));
+            }
+        }
+
+        Ok(ChunkMatches {
+            matches,
+            line_count,
+        })
+    }
+
     fn find_matches_from_reader<R: BufRead>(&self, reader: R) -> io::Result<Vec<LineMatch>> {
-        reader
-            .lines()
+        if self.lines.has_negative() {
+            // positions relative to the end need total line count, buffer whole input
+            let lines = lossy_lines(reader).collect::<io::Result<Vec<_>>>()?;
+            let ranges = self.lines.normalize(lines.len() as RangeCount);
+            self.find_matches_in_lines(lines.into_iter().map(Ok), &ranges)
+        } else {
+            self.find_matches_in_lines(lossy_lines(reader), self.lines)
+        }
+    }
+
+    fn find_matches_in_lines<I>(&self, lines: I, ranges: &RangeSpec) -> io::Result<Vec<LineMatch>>
+    where
+        I: Iterator<Item = io::Result<String>>,
+    {
+        // reading stops after last line of range, positions are resolved by now
+        let last_line = ranges.last_line().map_or(usize::MAX, |last| last.max(0) as usize);
+        lines
+            .take(last_line)
             .enumerate()
+            .filter(|(index, _)| ranges.contains((index + 1) as RangePos))
             .filter_map(|(index, line)| match line {
-                Ok(line) if self.is_match(&line) => Some(Ok(LineMatch {
-                    line,
-                    line_number: index + 1,
-                })),
+                Ok(line) if self.is_match(&line) => Some(Ok(self.line_match(line, index + 1))),
                 Ok(_) => None,
                 Err(e) => Some(Err(e)),
             })
@@ -78,4 +189,40 @@ impl<'a> MatchesFinder<'a> {
     fn is_match(&self, line: &str) -> bool {
         self.pattern.is_match(line) ^ self.invert_match
     }
+
+    fn line_match(&self, line: String, line_number: usize) -> LineMatch {
+        let spans = if self.invert_match {
+            vec![]
+        } else {
+            self.pattern.find_iter(&line).map(|m| m.range()).collect()
+        };
+
+        LineMatch {
+            line,
+            line_number,
+            spans,
+        }
+    }
+}
+
+/// Like `BufRead::lines`, but invalid UTF-8 is replaced with U+FFFD instead of being an error
+fn lossy_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = io::Result<String>> {
+    let mut buffer = vec![];
+
+    std::iter::from_fn(move || {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => None,
+            Ok(_) => {
+                if buffer.ends_with(b"\n") {
+                    buffer.pop();
+                    if buffer.ends_with(b"\r") {
+                        buffer.pop();
+                    }
+                }
+                Some(Ok(String::from_utf8_lossy(&buffer).into_owned()))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    })
 }
@@ -1,26 +1,43 @@
+use std::collections::HashMap;
 use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Instant;
 
+mod archive;
 mod args;
+mod chunk;
 mod error;
 mod finder;
 mod matcher;
+mod progress;
 mod reporter;
 
+use archive::ArchiveKind;
 pub use args::GrepArgs;
 pub use error::GrepError;
 use error::Result;
-use finder::FilesFinder;
+pub use finder::FilesFinder;
 use matcher::MatchesFinder;
+pub use matcher::{FileMatches, LineMatch};
+use progress::Progress;
 use reporter::FileMatchesReporter;
 
+use crate::cancel;
+use crate::report;
+
 pub fn grep(args: GrepArgs) -> Result<()> {
-    let stdout = io::stdout();
-    let mut writer = stdout.lock();
+    // colored checks terminal on its own, global --color choice has to override it
+    colored::control::set_override(args.color.enabled());
+    grep_to(&args, &mut io::stdout().lock())
+}
 
-    let has_matches = if args.files.is_empty() {
-        grep_stdin(&args, &mut writer)?
+/// Searches like `grep` but prints to given writer, colors follow `colored` settings
+pub fn grep_to<W: Write>(args: &GrepArgs, writer: &mut W) -> Result<()> {
+    let has_matches = if args.count_unique {
+        grep_count_unique(args, writer)?
+    } else if args.files.is_empty() {
+        grep_stdin(args, writer)?
     } else {
-        grep_files(&args, &mut writer)?
+        grep_files(args, writer)?
     };
     writer.flush()?;
 
@@ -31,6 +48,58 @@ pub fn grep(args: GrepArgs) -> Result<()> {
     }
 }
 
+/// Searches target files, or standard input when no files are given, without printing.
+/// Only files with matches are returned, the first unreadable file fails the whole search.
+pub fn search(args: &GrepArgs) -> Result<Vec<FileMatches>> {
+    if args.files.is_empty() {
+        let finder = MatchesFinder::from_args(args);
+        let result = finder.find_matches_from_stdin(io::stdin().lock())?;
+        return Ok(if result.is_empty() {
+            vec![]
+        } else {
+            vec![result]
+        });
+    }
+
+    let mut results = vec![];
+    for result in search_files(args) {
+        let result = result?;
+        if !result.is_empty() {
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Lazily searches every target file, errors carry the path of the failed file
+fn search_files(args: &GrepArgs) -> impl Iterator<Item = io::Result<FileMatches>> + '_ {
+    let files_finder = FilesFinder::from_args(args);
+    let matches_finder = MatchesFinder::from_args(args);
+
+    // files are searched while directories are still walked, so progress shows right away
+    files_finder.find_files().flat_map(move |file_path| {
+        let file_path = match file_path {
+            Ok(file_path) => file_path,
+            Err(e) => return vec![Err(e)],
+        };
+        let with_path =
+            |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", file_path.display(), e));
+
+        log::debug!("searching {}", file_path.display());
+        let archive_kind = ArchiveKind::from_path(&file_path).filter(|_| args.search_archives);
+        match archive_kind {
+            Some(kind) => {
+                match archive::find_matches_from_archive(&matches_finder, &file_path, kind) {
+                    Ok(results) => results.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(with_path(e))],
+                }
+            }
+            None => vec![matches_finder.find_matches_from_file(&file_path).map_err(with_path)],
+        }
+    })
+}
+
 fn grep_stdin<W: Write>(args: &GrepArgs, writer: &mut W) -> io::Result<bool> {
     let reader = std::io::stdin().lock();
     if reader.is_terminal() {
@@ -48,7 +117,7 @@ fn grep_piped_stdin<R: BufRead, W: Write>(
 ) -> io::Result<bool> {
     let finder = MatchesFinder::from_args(args);
     let result = finder.find_matches_from_stdin(&mut reader)?;
-    if !result.is_empty() {
+    if !result.is_empty() || args.include_zero {
         let mut reporter = FileMatchesReporter::new(args, writer);
         reporter.output_stdin_matches(&result)?;
     }
@@ -66,9 +135,10 @@ fn grep_interactive_stdin<R: BufRead, W: Write>(
     let mut reporter = FileMatchesReporter::new(args, writer);
 
     while reader.read_line(&mut buffer)? > 0 {
-        let line = buffer.trim_end();
+        let line = buffer.trim_end_matches(['\r', '\n']);
         reporter.output_line_text(line)?;
         buffer.clear();
+        cancel::check()?;
     }
 
     Ok(())
@@ -76,36 +146,79 @@ fn grep_interactive_stdin<R: BufRead, W: Write>(
 
 // TODO: multithreaded grep
 fn grep_files<W: Write>(args: &GrepArgs, writer: &mut W) -> io::Result<bool> {
-    let files_finder = FilesFinder::from_args(args);
-    let matches_finder = MatchesFinder::from_args(args);
     let mut reporter = FileMatchesReporter::new(args, writer);
+    let mut progress = Progress::new(args.progress);
 
+    let start = Instant::now();
     let mut has_matches = false;
-    for file_result in files_finder.find_files() {
+    let mut has_output = false;
+    let mut searched_files = 0;
+    let mut matches = 0;
+    for file_result in search_files(args) {
+        if cancel::is_cancelled() {
+            break;
+        }
+        if let Ok(result) = &file_result {
+            searched_files += 1;
+            matches += result.len();
+        }
         match file_result {
-            Ok(file_path) => match matches_finder.find_matches_from_file(&file_path) {
-                Ok(result) if !result.is_empty() => {
-                    if has_matches {
-                        reporter.output_file_separator()?;
-                    }
-                    reporter.output_file_matches(&result)?;
-                    has_matches = true;
+            Ok(result) if !result.is_empty() || args.include_zero => {
+                progress.clear()?;
+                if has_output {
+                    reporter.output_file_separator()?;
                 }
-                Ok(_) => continue,
-                Err(e) => {
-                    writeln!(
-                        io::stderr(),
-                        "Error reading file {}: {}",
-                        file_path.display(),
-                        e
-                    )?;
-                }
-            },
+                reporter.output_file_matches(&result)?;
+                reporter.flush()?;
+                has_output = true;
+                has_matches |= !result.is_empty();
+                progress.file_scanned(&result.file_path, result.len())?;
+            }
+            Ok(result) => progress.file_scanned(&result.file_path, 0)?,
             Err(e) => {
-                eprintln!("Error accessing file: {}", e);
+                progress.clear()?;
+                report::file_error("grep", e);
             }
         }
     }
+    progress.clear()?;
+    log::info!("searched files in {:?}", start.elapsed());
+    if cancel::is_cancelled() {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            format!("interrupted after searching {searched_files} files, {matches} matches found"),
+        ));
+    }
 
     Ok(has_matches)
 }
+
+/// Counts occurrences of every distinct matched string across all targets, like `uniq -c`
+fn grep_count_unique<W: Write>(args: &GrepArgs, writer: &mut W) -> io::Result<bool> {
+    let results = if args.files.is_empty() {
+        let finder = MatchesFinder::from_args(args);
+        vec![finder.find_matches_from_stdin(io::stdin().lock())?]
+    } else {
+        search_files(args)
+            .filter_map(|result| result.map_err(|e| report::file_error("grep", e)).ok())
+            .collect()
+    };
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line_match in results.iter().flat_map(|result| &result.matches) {
+        for span in &line_match.spans {
+            *counts.entry(&line_match.line[span.clone()]).or_default() += 1;
+        }
+    }
+
+    // most frequent values first, ties in lexical order
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+    let mut reporter = FileMatchesReporter::new(args, writer);
+    for (value, count) in &counts {
+        reporter.output_unique_count(value, *count)?;
+    }
+
+    Ok(!counts.is_empty())
+}
@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// minimal interval between two progress line renders
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress line written to stderr during long searches
+pub struct Progress {
+    enabled: bool,
+    start: Instant,
+    last_render: Option<Instant>,
+    files: usize,
+    matches: usize,
+    bytes: u64,
+    current_dir: PathBuf,
+    visible: bool,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            start: Instant::now(),
+            last_render: None,
+            files: 0,
+            matches: 0,
+            bytes: 0,
+            current_dir: PathBuf::new(),
+            visible: false,
+        }
+    }
+
+    pub fn file_scanned(&mut self, file_path: &Path, matches: usize) -> io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        self.files += 1;
+        self.matches += matches;
+        self.bytes += fs::metadata(file_path).map_or(0, |m| m.len());
+        if let Some(dir) = file_path.parent() {
+            self.current_dir = dir.to_path_buf();
+        }
+
+        let now = Instant::now();
+        match self.last_render {
+            Some(last) if now.duration_since(last) < REFRESH_INTERVAL => Ok(()),
+            _ => {
+                self.last_render = Some(now);
+                self.render()
+            }
+        }
+    }
+
+    /// erases progress line so that normal output starts at line beginning
+    pub fn clear(&mut self) -> io::Result<()> {
+        if self.visible {
+            self.visible = false;
+            let mut stderr = io::stderr().lock();
+            write!(stderr, "\r\x1b[2K")?;
+            stderr.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn render(&mut self) -> io::Result<()> {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let throughput = if elapsed > 0.0 {
+            self.bytes as f64 / elapsed / (1024.0 * 1024.0)
+        } else {
+            0.0
+        };
+
+        let mut stderr = io::stderr().lock();
+        write!(
+            stderr,
+            "\r\x1b[2K{} files, {} matches, {:.1} MiB/s, {}",
+            self.files,
+            self.matches,
+            throughput,
+            self.current_dir.display()
+        )?;
+        stderr.flush()?;
+        self.visible = true;
+
+        Ok(())
+    }
+}
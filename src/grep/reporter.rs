@@ -1,19 +1,28 @@
 use std::{
     borrow::Cow,
     io::{self, Write},
-    path::Path,
+    ops::Range,
+    path::{self, Component, Path, PathBuf},
 };
 
 use colored::Colorize;
 use regex::Regex;
 
-use super::args::GrepArgs;
+use super::args::{GrepArgs, PathStyle};
 use super::matcher::{FileMatches, LineMatch};
+use crate::paths;
+
+/// marks the part of a long line that's cut off by `--max-columns`
+const OMITTED_MARKER: &str = "[... omitted]";
 
 pub struct FileMatchesReporter<'a, W: Write> {
     pattern: &'a Regex,
     count: bool,
     color: bool,
+    trim: bool,
+    max_columns: Option<usize>,
+    path_style: Option<PathStyle>,
+    only_matching: bool,
     writer: &'a mut W,
 }
 
@@ -22,7 +31,11 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         Self {
             pattern: &args.pattern,
             count: args.count,
-            color: args.color,
+            color: args.color.enabled(),
+            trim: args.trim,
+            max_columns: args.max_columns,
+            path_style: args.path_style,
+            only_matching: args.only_matching,
             writer,
         }
     }
@@ -35,7 +48,7 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         }
     }
 
-    pub fn output_stdin_matches(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
+    pub fn output_stdin_matches(&mut self, result: &FileMatches) -> io::Result<()> {
         if self.count {
             self.output_matches_count(result)
         } else {
@@ -43,7 +56,7 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         }
     }
 
-    pub fn output_file_matches(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
+    pub fn output_file_matches(&mut self, result: &FileMatches) -> io::Result<()> {
         if self.count {
             self.output_file_match_count(result)
         } else {
@@ -51,18 +64,18 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         }
     }
 
-    fn output_matches_count(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
+    fn output_matches_count(&mut self, result: &FileMatches) -> io::Result<()> {
         write!(self.writer, "{}", result.len())?;
         self.output_newline()
     }
 
-    fn output_file_match_count(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
+    fn output_file_match_count(&mut self, result: &FileMatches) -> io::Result<()> {
         self.output_file_path(&result.file_path)?;
         write!(self.writer, ":")?;
         self.output_matches_count(result)
     }
 
-    fn output_file_matched_lines(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
+    fn output_file_matched_lines(&mut self, result: &FileMatches) -> io::Result<()> {
         self.output_file_path(&result.file_path)?;
         self.output_newline()?;
         self.output_matched_lines(result)?;
@@ -70,17 +83,58 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         Ok(())
     }
 
-    fn output_matched_lines(&mut self, result: &FileMatches<'_>) -> io::Result<()> {
-        for LineMatch { line, line_number } in &result.matches {
+    fn output_matched_lines(&mut self, result: &FileMatches) -> io::Result<()> {
+        for LineMatch {
+            line,
+            line_number,
+            spans,
+        } in &result.matches
+        {
+            if self.only_matching {
+                for span in spans {
+                    self.output_line_number(*line_number)?;
+                    self.output_line_text(&line[span.clone()])?;
+                }
+                continue;
+            }
+
             self.output_line_number(*line_number)?;
-            self.output_line_text(line)?;
+            match self.max_columns {
+                Some(max_columns) if line.chars().count() > max_columns => {
+                    self.output_truncated_line_text(line, spans, max_columns)?
+                }
+                _ => self.output_line_text(line)?,
+            }
         }
 
         Ok(())
     }
 
+    fn output_truncated_line_text(
+        &mut self,
+        line: &str,
+        spans: &[Range<usize>],
+        max_columns: usize,
+    ) -> io::Result<()> {
+        let focus = spans.first().map_or(0, |span| span.start);
+        let window = truncate_window(line, focus, max_columns);
+
+        if window.start > 0 {
+            write!(self.writer, "{} ", OMITTED_MARKER)?;
+        }
+        self.output_text(&line[window.clone()])?;
+        if window.end < line.len() {
+            write!(self.writer, " {}", OMITTED_MARKER)?;
+        }
+        self.output_newline()
+    }
+
     fn output_file_path(&mut self, path: &Path) -> io::Result<()> {
-        let path = path.to_string_lossy();
+        let path = match self.path_style {
+            Some(style) => styled_path(path, style),
+            None => path.to_path_buf(),
+        };
+        let path = paths::native(&path);
         if self.color {
             write!(self.writer, "{}", path.magenta().bold())
         } else {
@@ -96,19 +150,33 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         }
     }
 
+    pub fn output_unique_count(&mut self, value: &str, count: usize) -> io::Result<()> {
+        write!(self.writer, "{:>7} ", count)?;
+        self.output_line_text(value)
+    }
+
     pub fn output_line_text(&mut self, line: &str) -> io::Result<()> {
+        self.output_text(line)?;
+        self.output_newline()
+    }
+
+    fn output_text(&mut self, text: &str) -> io::Result<()> {
+        let text = if self.trim { text.trim() } else { text };
         if self.color {
-            write!(self.writer, "{}", self.highlight_pattern(line.trim()))?;
+            write!(self.writer, "{}", self.highlight_pattern(text))
         } else {
-            write!(self.writer, "{}", line.trim())?;
+            write!(self.writer, "{}", text)
         }
-        self.output_newline()
     }
 
     fn output_newline(&mut self) -> io::Result<()> {
         writeln!(self.writer)
     }
 
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
     fn highlight_pattern<'b>(&self, line: &'b str) -> Cow<'b, str> {
         if self.color && self.pattern.is_match(line) {
             self.pattern.replace_all(line, "$0".red().to_string())
@@ -117,3 +185,107 @@ impl<'a, W: Write> FileMatchesReporter<'a, W> {
         }
     }
 }
+
+fn styled_path(path: &Path, style: PathStyle) -> PathBuf {
+    let Ok(absolute) = path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let absolute = normalize_path(&absolute);
+
+    match style {
+        PathStyle::Absolute => absolute,
+        PathStyle::Relative => match std::env::current_dir() {
+            Ok(base) => relative_path(&absolute, &base),
+            Err(_) => absolute,
+        },
+    }
+}
+
+/// Lexically resolves `.` and `..` components without touching file system
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Path of absolute `path` relative to absolute directory `base`
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+    let common = path_components.iter().zip(&base_components).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base_components.len() {
+        relative.push("..");
+    }
+    relative.extend(&path_components[common..]);
+
+    relative
+}
+
+/// Byte range of at most `max_columns` characters of line, keeping byte offset `focus` visible
+fn truncate_window(line: &str, focus: usize, max_columns: usize) -> Range<usize> {
+    let total = line.chars().count();
+    let focus_char = line[..focus].chars().count();
+    // leave some context before the focused match
+    let start_char = focus_char
+        .saturating_sub(max_columns / 4)
+        .min(total.saturating_sub(max_columns));
+
+    let mut boundaries = line.char_indices().map(|(i, _)| i).chain([line.len()]);
+    let start = boundaries.nth(start_char).unwrap_or(line.len());
+    let end = boundaries.nth(max_columns.saturating_sub(1)).unwrap_or(line.len());
+
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_window() {
+        let line = "0123456789abcdefghij";
+        assert_eq!(truncate_window(line, 0, 8), 0..8);
+        assert_eq!(truncate_window(line, 10, 8), 8..16);
+        assert_eq!(truncate_window(line, 19, 8), 12..20);
+        assert_eq!(&line[truncate_window(line, 10, 8)], "89abcdef");
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let base = Path::new("/home/user/project");
+        assert_eq!(
+            relative_path(Path::new("/home/user/project/src/main.rs"), base),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/home/user/other/a.txt"), base),
+            PathBuf::from("../other/a.txt")
+        );
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path(Path::new("/a/b/./../c/d.txt")),
+            PathBuf::from("/a/c/d.txt")
+        );
+    }
+
+    #[test]
+    fn test_truncate_window_multibyte() {
+        let line = "一二三四五六七八九十";
+        let window = truncate_window(line, "一二三四五".len(), 4);
+        assert_eq!(&line[window], "五六七八");
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::view::{self, Paging, RangePos, RangeSpec, ViewArgs};
+
+/// lines printed when neither `-n` nor `-c` is given, same as coreutils
+const DEFAULT_LINES: &str = "10";
+
+#[derive(Debug, Parser)]
+pub struct HeadArgs {
+    #[arg(
+        index = 1,
+        help = "Files to print beginning of, standard input when not specified or file is '-'"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "[-]N",
+        default_value = DEFAULT_LINES,
+        allow_hyphen_values = true,
+        value_parser = parse_head_count,
+        help = "Print first N lines, or all but last N with '-N'"
+    )]
+    pub lines: RangeSpec,
+
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "[-]N",
+        allow_hyphen_values = true,
+        value_parser = parse_head_count,
+        help = "Print first N bytes instead of lines, or all but last N with '-N'"
+    )]
+    pub bytes: Option<RangeSpec>,
+
+    #[arg(short, long, help = "Never print headers of file names")]
+    pub quiet: bool,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with = "quiet",
+        help = "Always print headers of file names"
+    )]
+    pub verbose: bool,
+}
+
+impl HeadArgs {
+    /// Same selection made with `kf view`, whose line and byte ranges do the work
+    pub fn into_view_args(self) -> ViewArgs {
+        let (lines, bytes) = match self.bytes {
+            Some(bytes) => (vec![], Some(bytes)),
+            None => (vec![self.lines], None),
+        };
+        ViewArgs {
+            file_paths: self.file_paths,
+            lines,
+            bytes,
+            quite: self.quiet,
+            always_header: self.verbose,
+            paging: Paging::Never,
+            // bytes are passed through as they are, like coreutils
+            force_text: true,
+            ..ViewArgs::default()
+        }
+    }
+}
+
+pub fn head(args: HeadArgs) -> view::Result<()> {
+    view::view_files(args.into_view_args())
+}
+
+/// `N` selects `..N`, `-N` selects all but last N like `..-(N+1)`
+fn parse_head_count(value: &str) -> Result<RangeSpec, String> {
+    let (all_but_last, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let count: RangePos = digits
+        .parse()
+        .ok()
+        .filter(|&count| count >= 0)
+        .ok_or_else(|| format!("invalid count '{}'", value))?;
+
+    Ok(if all_but_last {
+        RangeSpec::To(-count - 1)
+    } else {
+        RangeSpec::To(count)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head_count() {
+        assert_eq!(parse_head_count("5"), Ok(RangeSpec::To(5)));
+        assert_eq!(parse_head_count("0"), Ok(RangeSpec::To(0)));
+        assert_eq!(parse_head_count("-2"), Ok(RangeSpec::To(-3)));
+        assert!(parse_head_count("x").is_err());
+        assert!(parse_head_count("--1").is_err());
+    }
+}
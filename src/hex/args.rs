@@ -0,0 +1,473 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Args, Parser, ValueEnum, builder::TypedValueParser};
+use colored::Color;
+
+use super::decode::parse_offset;
+use super::fields::FieldList;
+use super::patch::Patch;
+use crate::color::ColorWhen;
+use crate::view::{Paging, RangeSpec};
+
+pub const BYTES_PER_LINE: usize = 16;
+
+pub const DEFAULT_ZERO_PLACEHOLDER: &str = ".";
+pub const DEFAULT_FF_PLACEHOLDER: &str = "##";
+
+const DEFAULT_HIGHLIGHT_COLOR: Color = Color::Yellow;
+
+#[derive(Debug, Parser)]
+pub struct HexArgs {
+    #[arg(
+        index = 1,
+        required = true,
+        help = "Files to view in hex format, a header is printed before each of multiple files"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'c',
+        long,
+        visible_alias = "columns",
+        value_name = "N|auto",
+        help = "bytes per line for hex view, 'auto' fits terminal width",
+        default_value_t = Columns::Count(BYTES_PER_LINE),
+        value_parser = parse_columns
+    )]
+    pub bytes_per_line: Columns,
+
+    #[arg(
+        short,
+        long,
+        default_value_t = 1,
+        value_parser = group_size_parser(),
+        help = "Bytes per group, groups are separated by an extra space"
+    )]
+    pub group_size: usize,
+
+    #[command(flatten)]
+    pub style: DumpStyleArgs,
+
+    #[arg(
+        short,
+        long,
+        value_name = "OFFSET",
+        default_value_t = 0,
+        value_parser = parse_offset,
+        conflicts_with_all = ["bytes", "diff", "find", "find_text"],
+        help = "Start dump at offset like '4096' or '0x1000'"
+    )]
+    pub skip: u64,
+
+    #[arg(
+        long,
+        value_name = "SPEC",
+        allow_hyphen_values = true,
+        value_parser = clap::value_parser!(RangeSpec),
+        conflicts_with_all = ["diff", "find", "find_text", "plain"],
+        help = "Bytes to dump, positions start from 1 like 'view -c', e.g. '0x100..0x1ff,-16..' or '~1..64'"
+    )]
+    pub bytes: Option<RangeSpec>,
+
+    #[arg(
+        long,
+        help = "Dump only lines where two given files differ, with differing bytes highlighted"
+    )]
+    pub diff: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["diff", "bytes", "skip", "patch"],
+        help = "Compare two given files side by side, inserted or deleted bytes are shown as gaps"
+    )]
+    pub compare_aligned: bool,
+
+    #[arg(
+        long,
+        value_name = "OFFSET=HEXBYTES",
+        value_parser = clap::value_parser!(Patch),
+        conflicts_with_all = ["diff", "find", "find_text", "bytes", "plain"],
+        help = "Overwrite bytes at offset in place, like '0x10=dead beef', repeat for more patches"
+    )]
+    pub patch: Vec<Patch>,
+
+    /// set from global `--dry-run`, shows lines changed by --patch before and after without
+    /// writing file
+    #[arg(skip)]
+    pub dry_run: bool,
+
+    #[arg(
+        long,
+        value_name = "HEXSTRING",
+        value_parser = |value: &str| parse_hex_bytes(value).map(HexBytes),
+        help = "Print offsets of byte pattern like 'dead beef' with surrounding lines"
+    )]
+    pub find: Option<HexBytes>,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with = "find",
+        value_parser = clap::builder::NonEmptyStringValueParser::new(),
+        help = "Print offsets of text with surrounding lines"
+    )]
+    pub find_text: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "OFFSET",
+        value_parser = parse_offset,
+        conflicts_with_all = ["diff", "find", "find_text", "plain"],
+        help = "After dump, print bytes at offset like '0x10' decoded as numbers of both byte orders"
+    )]
+    pub decode: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SPEC",
+        value_parser = clap::value_parser!(FieldList),
+        conflicts_with_all = ["diff", "find", "find_text", "plain"],
+        help = "Before dump, print header fields decoded from file start, e.g. 'u32:magic,u16be:version,bytes16:uuid,str8:name'"
+    )]
+    pub fields: Option<FieldList>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ALGORITHM",
+        help = "Print checksum of whole file after dump"
+    )]
+    pub checksum: Option<ChecksumKind>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Print checksum of every N byte block with its offsets after dump, crc32 unless --checksum is given"
+    )]
+    pub block_checksum: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "START..END[:COLOR]",
+        value_parser = clap::value_parser!(Highlight),
+        help = "Color background of bytes between offsets shown in dump, like '0x0..0xf:blue', repeat for more ranges"
+    )]
+    pub highlight: Vec<Highlight>,
+
+    #[arg(
+        long,
+        help = "Show entropy of every line as a bar, high for compressed or encrypted data"
+    )]
+    pub entropy: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["endian", "group_size", "diff", "find", "find_text", "entropy"],
+        help = "Print only hex digits, without offsets and text, for scripting like 'xxd -p'"
+    )]
+    pub plain: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = HexFormat::Dump,
+        help = "Output format"
+    )]
+    pub format: HexFormat,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 76,
+        help = "Characters per line of base64 format, 0 for a single line"
+    )]
+    pub wrap: usize,
+
+    #[arg(
+        long,
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = Paging::Auto,
+        help = "Page dump of a single file screen by screen, with commands to scroll and jump"
+    )]
+    pub paging: Paging,
+
+    #[arg(
+        long,
+        help = "Don't show progress on stderr while dumping to a file or pipe"
+    )]
+    pub quiet_progress: bool,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["diff", "find", "find_text", "patch", "skip", "bytes"],
+        help = "Decode files of base64 or uu format back to binary"
+    )]
+    pub reverse: bool,
+
+    /// set from global `--color`
+    #[arg(skip)]
+    pub color: ColorWhen,
+}
+
+/// Look of dump lines, shared by `hex` and `view --format hex`
+#[derive(Debug, Clone, Args)]
+pub struct DumpStyleArgs {
+    #[arg(
+        long,
+        value_enum,
+        help = "Render each group as a word of given byte order, like 'xxd -e'"
+    )]
+    pub endian: Option<Endian>,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        default_value = DEFAULT_ZERO_PLACEHOLDER,
+        value_parser = parse_placeholder,
+        help = "Shown instead of hex digits of 0x00 bytes"
+    )]
+    pub zero_placeholder: String,
+
+    #[arg(
+        long,
+        value_name = "TEXT",
+        default_value = DEFAULT_FF_PLACEHOLDER,
+        value_parser = parse_placeholder,
+        help = "Shown instead of hex digits of 0xff bytes"
+    )]
+    pub ff_placeholder: String,
+
+    #[arg(long, help = "Show hex digits of every byte, ignoring placeholders")]
+    pub no_placeholders: bool,
+
+    #[arg(
+        long,
+        value_name = "ENCODING",
+        value_enum,
+        default_value_t = TextEncoding::Ascii,
+        help = "Encoding of text column, utf8 shows multibyte characters instead of dots"
+    )]
+    pub text_encoding: TextEncoding,
+
+    #[arg(
+        long,
+        value_name = "BASE",
+        value_enum,
+        default_value_t = OffsetBase::Hex,
+        help = "Number base of offset column"
+    )]
+    pub offset_base: OffsetBase,
+}
+
+impl HexFormat {
+    /// Formats rendered as dump lines with offsets and text column
+    pub fn is_dump(self) -> bool {
+        matches!(self, HexFormat::Dump | HexFormat::Octal)
+    }
+}
+
+impl HexArgs {
+    /// bytes searched by `--find` or `--find-text`
+    pub fn find_pattern(&self) -> Option<&[u8]> {
+        match (&self.find, &self.find_text) {
+            (Some(HexBytes(bytes)), _) => Some(bytes),
+            (None, Some(text)) => Some(text.as_bytes()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Bytes per line, either given or fitted to terminal width
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Columns {
+    Count(usize),
+    Auto,
+}
+
+impl fmt::Display for Columns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Columns::Count(count) => write!(f, "{}", count),
+            Columns::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+pub fn parse_columns(value: &str) -> Result<Columns, String> {
+    if value == "auto" {
+        return Ok(Columns::Auto);
+    }
+
+    match value.parse() {
+        Ok(0) | Err(_) => Err("expected 'auto' or a positive number".to_string()),
+        Ok(count) => Ok(Columns::Count(count)),
+    }
+}
+
+/// Group sizes dividing a line into words
+pub fn group_size_parser() -> impl TypedValueParser<Value = usize> {
+    clap::builder::PossibleValuesParser::new(["1", "2", "4", "8"])
+        .map(|size| size.parse::<usize>().expect("group size is a number"))
+}
+
+/// Inclusive range of byte offsets colored by `--highlight`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    pub start: u64,
+    pub end: u64,
+    pub color: Color,
+}
+
+impl FromStr for Highlight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (range, color) = match s.split_once(':') {
+            Some((range, name)) => {
+                let color = name.parse().map_err(|_| format!("unknown color '{}'", name))?;
+                (range, color)
+            }
+            None => (s, DEFAULT_HIGHLIGHT_COLOR),
+        };
+        let (start, end) = range
+            .split_once("..")
+            .ok_or_else(|| "highlight should be like 'START..END[:COLOR]'".to_string())?;
+        let (start, end) = (parse_offset(start)?, parse_offset(end)?);
+        if start > end {
+            return Err(format!("highlight start {} is after end {}", start, end));
+        }
+
+        Ok(Highlight { start, end, color })
+    }
+}
+
+/// Placeholder takes the place of two hex digits, so it's one or two characters
+fn parse_placeholder(value: &str) -> Result<String, String> {
+    match value.chars().count() {
+        1 | 2 => Ok(value.to_string()),
+        _ => Err("placeholder should be one or two characters".to_string()),
+    }
+}
+
+/// Bytes given as hex digits on command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct HexBytes(pub Vec<u8>);
+
+/// Parses hex digits like "0xdeadbeef" or "de ad be ef" into bytes
+pub fn parse_hex_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let digits: String = value.split_whitespace().collect();
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+
+    if digits.is_empty() {
+        return Err("empty byte pattern".to_string());
+    }
+    // digits are sliced by byte offsets below
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii()) {
+        return Err(format!("invalid hex digit '{}'", c));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".to_string());
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("invalid hex byte '{}'", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OffsetBase {
+    Hex,
+    Dec,
+    Oct,
+    /// Omit offset column
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum HexFormat {
+    /// Offset, hex bytes and ASCII text of every line
+    Dump,
+    /// Like dump, but bytes are 3 octal digits like 'od -b'
+    Octal,
+    /// C 'unsigned char' array with length variable, like 'xxd -i'
+    CArray,
+    /// Base64 text wrapped at --wrap characters
+    Base64,
+    /// Uuencoded text between 'begin' and 'end' lines
+    Uu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TextEncoding {
+    /// Printable ASCII characters, other bytes are shown as '.'
+    Ascii,
+    /// Also multibyte UTF-8 characters, padded to the width of their bytes
+    Utf8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ChecksumKind {
+    Crc32,
+    Sha256,
+}
+
+impl fmt::Display for ChecksumKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChecksumKind::Crc32 => write!(f, "crc32"),
+            ChecksumKind::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_highlight() {
+        assert_eq!(
+            "0x10..0x1f:blue".parse::<Highlight>(),
+            Ok(Highlight {
+                start: 16,
+                end: 31,
+                color: Color::Blue,
+            })
+        );
+        assert_eq!(
+            "4..8".parse::<Highlight>().unwrap().color,
+            DEFAULT_HIGHLIGHT_COLOR
+        );
+        assert!("8..4".parse::<Highlight>().is_err());
+        assert!("4..8:nocolor".parse::<Highlight>().is_err());
+        assert!("4".parse::<Highlight>().is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_bytes() {
+        assert_eq!(
+            parse_hex_bytes("0xdeadBEEF"),
+            Ok(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(parse_hex_bytes("00 ff"), Ok(vec![0x00, 0xff]));
+        assert!(parse_hex_bytes("abc").is_err());
+        assert!(parse_hex_bytes("zz").is_err());
+        assert!(parse_hex_bytes("").is_err());
+        assert!(parse_hex_bytes("aéb").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+use super::error::{HexError, Result};
+use super::read_full;
+
+/// bytes per line of array initializer, same as `xxd -i`
+const BYTES_PER_LINE: usize = 12;
+
+/// Writes content as C `unsigned char` array named after file, followed by its length
+pub fn write_c_array<R: Read, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let name = c_identifier(file_path);
+    let mut buffer = [0; BYTES_PER_LINE];
+    let mut len: u64 = 0;
+
+    writeln!(writer, "unsigned char {}[] = {{", name)?;
+    loop {
+        let n = read_full(reader, &mut buffer).map_err(HexError::read_failed(file_path, len))?;
+        if n == 0 {
+            break;
+        }
+        if len > 0 {
+            writeln!(writer, ",")?;
+        }
+
+        let bytes: Vec<String> = buffer[..n].iter().map(|byte| format!("0x{:02x}", byte)).collect();
+        write!(writer, "  {}", bytes.join(", "))?;
+        len += n as u64;
+    }
+    if len > 0 {
+        writeln!(writer)?;
+    }
+    writeln!(writer, "}};")?;
+    writeln!(writer, "unsigned int {}_len = {};", name, len)?;
+
+    Ok(())
+}
+
+/// File path turned into valid C identifier, like `xxd -i` does
+fn c_identifier(file_path: &Path) -> String {
+    let mut name: String = file_path
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_identifier() {
+        assert_eq!(c_identifier(Path::new("fw/boot-1.bin")), "fw_boot_1_bin");
+        assert_eq!(c_identifier(Path::new("1.bin")), "_1_bin");
+    }
+
+    #[test]
+    fn test_write_c_array() {
+        let mut output = vec![];
+        let mut input: &[u8] = &[0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 255];
+        write_c_array(Path::new("a.bin"), &mut input, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "unsigned char a_bin[] = {\n  \
+             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,\n  \
+             0xff\n};\nunsigned int a_bin_len = 13;\n"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::{fs::File, io::Write, path::Path};
+
+use sha2::{Digest, Sha256};
+
+use super::args::{ChecksumKind, HexArgs};
+use super::error::{HexError, Result};
+use super::read_full;
+
+/// buffer size to hash whole file with
+const CHUNK_SIZE: usize = 64 * 1024;
+
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(kind: ChecksumKind) -> Self {
+        match kind {
+            ChecksumKind::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            ChecksumKind::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Crc32(hasher) => hasher.update(bytes),
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+        }
+    }
+
+    /// Checksum as lowercase hex digits
+    fn finish(self) -> String {
+        match self {
+            Hasher::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+            Hasher::Sha256(hasher) => {
+                hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+            }
+        }
+    }
+}
+
+/// Prints checksum of every `--block-checksum` block and of whole file after dump,
+/// blocks use crc32 unless `--checksum` picks another algorithm
+pub fn write_checksums<W: Write>(file_path: &Path, args: &HexArgs, writer: &mut W) -> Result<()> {
+    let kind = args.checksum.unwrap_or(ChecksumKind::Crc32);
+    let block_size = args.block_checksum.unwrap_or(CHUNK_SIZE as u64) as usize;
+    let mut file = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+    let mut buffer = vec![0; block_size];
+    let mut file_hasher = Hasher::new(kind);
+    let mut offset: u64 = 0;
+
+    loop {
+        let n =
+            read_full(&mut file, &mut buffer).map_err(HexError::read_failed(file_path, offset))?;
+        if n == 0 {
+            break;
+        }
+
+        let block = &buffer[..n];
+        file_hasher.update(block);
+        if args.block_checksum.is_some() {
+            let mut block_hasher = Hasher::new(kind);
+            block_hasher.update(block);
+            let last = offset + n as u64 - 1;
+            writeln!(
+                writer,
+                "block [0x{:08x}..0x{:08x}] {}",
+                offset,
+                last,
+                block_hasher.finish()
+            )?;
+        }
+        offset += n as u64;
+    }
+
+    if let Some(kind) = args.checksum {
+        writeln!(writer, "{}: {}", kind, file_hasher.finish())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum(kind: ChecksumKind, chunks: &[&[u8]]) -> String {
+        let mut hasher = Hasher::new(kind);
+        for chunk in chunks {
+            hasher.update(chunk);
+        }
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(
+            checksum(ChecksumKind::Crc32, &[b"1234", b"56789"]),
+            "cbf43926"
+        );
+        assert_eq!(
+            checksum(ChecksumKind::Sha256, &[b"abc"]),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::{fs, io::Write, path::Path};
+
+use colored::Colorize;
+
+use super::args::HexArgs;
+use super::error::{HexError, Result};
+use super::format::HexRenderer;
+
+/// bytes that have to match for both files to be considered in sync again
+const SYNC_LEN: usize = 4;
+
+/// farthest an insertion or deletion is searched for from a differing byte
+const SEARCH_WINDOW: usize = 256;
+
+/// Position of aligned files, bytes only in one of them leave a gap in the other
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aligned {
+    Same(u8),
+    Changed(u8, u8),
+    /// only in first file
+    Deleted(u8),
+    /// only in second file
+    Inserted(u8),
+}
+
+impl Aligned {
+    fn left(self) -> Option<u8> {
+        match self {
+            Aligned::Same(byte) | Aligned::Changed(byte, _) | Aligned::Deleted(byte) => Some(byte),
+            Aligned::Inserted(_) => None,
+        }
+    }
+
+    fn right(self) -> Option<u8> {
+        match self {
+            Aligned::Same(byte) | Aligned::Changed(_, byte) | Aligned::Inserted(byte) => Some(byte),
+            Aligned::Deleted(_) => None,
+        }
+    }
+}
+
+/// Prints rows of two files side by side where they differ, aligned so that inserted or deleted
+/// bytes show up as '--' gaps instead of shifting every following row. Skipped identical rows
+/// are marked with '*'. Both files are read into memory.
+pub fn compare_aligned<W: Write>(
+    left_path: &Path,
+    right_path: &Path,
+    args: &HexArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let left = fs::read(left_path).map_err(HexError::open_failed(left_path))?;
+    let right = fs::read(right_path).map_err(HexError::open_failed(right_path))?;
+    let aligned = align(&left, &right);
+    let bytes_per_line = HexRenderer::from_args(args).bytes_per_line();
+    let color = args.color.enabled();
+
+    let (mut left_offset, mut right_offset) = (0, 0);
+    let mut skipped = false;
+    for (i, row) in aligned.chunks(bytes_per_line).enumerate() {
+        if row.iter().all(|entry| matches!(entry, Aligned::Same(_))) {
+            skipped = true;
+        } else {
+            if skipped && i > 0 {
+                writeln!(writer, "*")?;
+            }
+            skipped = false;
+            let left_cells = format_cells(row, bytes_per_line, Aligned::left, color);
+            let right_cells = format_cells(row, bytes_per_line, Aligned::right, color);
+            writeln!(
+                writer,
+                "[0x{:08x}] {} | [0x{:08x}] {}",
+                left_offset,
+                left_cells,
+                right_offset,
+                right_cells.trim_end()
+            )?;
+        }
+        left_offset += row.iter().filter(|entry| entry.left().is_some()).count();
+        right_offset += row.iter().filter(|entry| entry.right().is_some()).count();
+    }
+    if skipped && aligned.len() > bytes_per_line {
+        writeln!(writer, "*")?;
+    }
+
+    let count = |predicate: fn(&Aligned) -> bool| aligned.iter().filter(|e| predicate(e)).count();
+    writeln!(
+        writer,
+        "{} bytes changed, {} deleted, {} inserted",
+        count(|entry| matches!(entry, Aligned::Changed(..))),
+        count(|entry| matches!(entry, Aligned::Deleted(_))),
+        count(|entry| matches!(entry, Aligned::Inserted(_)))
+    )?;
+
+    Ok(())
+}
+
+/// Hex cells of one side of a row, differing bytes highlighted, short last row padded
+fn format_cells(
+    row: &[Aligned],
+    bytes_per_line: usize,
+    side: fn(Aligned) -> Option<u8>,
+    color: bool,
+) -> String {
+    let mut cells = String::new();
+    for i in 0..bytes_per_line {
+        let cell = match row.get(i) {
+            None => "  ".to_string(),
+            Some(entry) => match side(*entry) {
+                None => "--".to_string(),
+                Some(byte) if color && !matches!(entry, Aligned::Same(_)) => {
+                    format!("{:02x}", byte).red().bold().to_string()
+                }
+                Some(byte) => format!("{:02x}", byte),
+            },
+        };
+        cells.push_str(&cell);
+        cells.push(' ');
+    }
+    cells
+}
+
+/// Greedy byte alignment, at a difference looks for the nearest position where both files
+/// are in sync again. A single changed byte is preferred, then the shortest insertion or
+/// deletion within `SEARCH_WINDOW`.
+fn align(left: &[u8], right: &[u8]) -> Vec<Aligned> {
+    let mut aligned = Vec::with_capacity(left.len().max(right.len()));
+    let (mut i, mut j) = (0, 0);
+
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            aligned.push(Aligned::Same(left[i]));
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        if in_sync(left, i + 1, right, j + 1) {
+            aligned.push(Aligned::Changed(left[i], right[j]));
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let shift = (1..=SEARCH_WINDOW).find_map(|d| {
+            if in_sync(left, i + d, right, j) {
+                Some((d, 0))
+            } else if in_sync(left, i, right, j + d) {
+                Some((0, d))
+            } else {
+                None
+            }
+        });
+        match shift {
+            Some((deleted, inserted)) => {
+                aligned.extend(left[i..i + deleted].iter().map(|&b| Aligned::Deleted(b)));
+                aligned.extend(right[j..j + inserted].iter().map(|&b| Aligned::Inserted(b)));
+                i += deleted;
+                j += inserted;
+            }
+            None => {
+                aligned.push(Aligned::Changed(left[i], right[j]));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    aligned.extend(left[i..].iter().map(|&b| Aligned::Deleted(b)));
+    aligned.extend(right[j..].iter().map(|&b| Aligned::Inserted(b)));
+
+    aligned
+}
+
+/// Whether next `SYNC_LEN` bytes match, or the rest of both files when fewer remain
+fn in_sync(left: &[u8], i: usize, right: &[u8], j: usize) -> bool {
+    let (Some(left), Some(right)) = (left.get(i..), right.get(j..)) else {
+        return false;
+    };
+    let len = SYNC_LEN.min(left.len()).min(right.len());
+    left[..len] == right[..len] && (len == SYNC_LEN || left.len() == right.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_insertion() {
+        let aligned = align(b"abcdefgh", b"abcXYdefgh");
+        assert_eq!(aligned[3], Aligned::Inserted(b'X'));
+        assert_eq!(aligned[4], Aligned::Inserted(b'Y'));
+        assert!(aligned[5..].iter().all(|entry| matches!(entry, Aligned::Same(_))));
+    }
+
+    #[test]
+    fn test_align_deletion_and_change() {
+        let aligned = align(b"abcdefghij", b"abdefgZij");
+        assert_eq!(aligned[2], Aligned::Deleted(b'c'));
+        assert_eq!(aligned[7], Aligned::Changed(b'h', b'Z'));
+        assert_eq!(aligned.len(), 10);
+    }
+
+    #[test]
+    fn test_align_tail() {
+        assert_eq!(
+            align(b"ab", b"abc"),
+            [
+                Aligned::Same(b'a'),
+                Aligned::Same(b'b'),
+                Aligned::Inserted(b'c')
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::args::Endian;
+use super::error::{HexError, Result};
+use super::read_full;
+
+/// widest type decoded, u64 and f64
+const MAX_WIDTH: usize = 8;
+
+/// Prints bytes at `offset` interpreted as integers and floats of both byte orders,
+/// types wider than the remaining bytes are shown as '-'
+pub fn write_decode_panel<W: Write>(file_path: &Path, offset: u64, writer: &mut W) -> Result<()> {
+    let mut file = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+    file.seek(SeekFrom::Start(offset))
+        .map_err(HexError::seek_failed(file_path, offset))?;
+    let mut buffer = [0; MAX_WIDTH];
+    let n = read_full(&mut file, &mut buffer).map_err(HexError::read_failed(file_path, offset))?;
+
+    writeln!(writer, "decoded at 0x{:08x}:", offset)?;
+    writeln!(
+        writer,
+        "  {:<4} {:>24} {:>24}",
+        "type", "little endian", "big endian"
+    )?;
+    for (name, little, big) in decode_rows(&buffer[..n]) {
+        writeln!(writer, "  {:<4} {:>24} {:>24}", name, little, big)?;
+    }
+
+    Ok(())
+}
+
+/// Type name with its little and big endian values
+fn decode_rows(bytes: &[u8]) -> Vec<(&'static str, String, String)> {
+    NumberType::ALL
+        .iter()
+        .map(|ty| {
+            let little = ty.decode(bytes, Endian::Little);
+            let big = ty.decode(bytes, Endian::Big);
+            match little.zip(big) {
+                Some((little, big)) => (ty.name(), little, big),
+                None => (ty.name(), "-".to_string(), "-".to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Fixed width number types bytes can be decoded as
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl NumberType {
+    pub const ALL: [NumberType; 10] = [
+        NumberType::U8,
+        NumberType::I8,
+        NumberType::U16,
+        NumberType::I16,
+        NumberType::U32,
+        NumberType::I32,
+        NumberType::U64,
+        NumberType::I64,
+        NumberType::F32,
+        NumberType::F64,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberType::U8 => "u8",
+            NumberType::I8 => "i8",
+            NumberType::U16 => "u16",
+            NumberType::I16 => "i16",
+            NumberType::U32 => "u32",
+            NumberType::I32 => "i32",
+            NumberType::U64 => "u64",
+            NumberType::I64 => "i64",
+            NumberType::F32 => "f32",
+            NumberType::F64 => "f64",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ty| ty.name() == name)
+    }
+
+    pub fn width(self) -> usize {
+        match self {
+            NumberType::U8 | NumberType::I8 => 1,
+            NumberType::U16 | NumberType::I16 => 2,
+            NumberType::U32 | NumberType::I32 | NumberType::F32 => 4,
+            NumberType::U64 | NumberType::I64 | NumberType::F64 => 8,
+        }
+    }
+
+    /// Value of leading bytes, `None` when there are fewer bytes than type width
+    pub fn decode(self, bytes: &[u8], endian: Endian) -> Option<String> {
+        macro_rules! decode {
+            ($ty:ty, $format:expr) => {{
+                let bytes = bytes.get(..self.width())?.try_into().expect("slice has type width");
+                let value = match endian {
+                    Endian::Little => <$ty>::from_le_bytes(bytes),
+                    Endian::Big => <$ty>::from_be_bytes(bytes),
+                };
+                $format(value)
+            }};
+            ($ty:ty) => {
+                decode!($ty, |value: $ty| value.to_string())
+            };
+        }
+
+        let value = match self {
+            NumberType::U8 => decode!(u8),
+            NumberType::I8 => decode!(i8),
+            NumberType::U16 => decode!(u16),
+            NumberType::I16 => decode!(i16),
+            NumberType::U32 => decode!(u32),
+            NumberType::I32 => decode!(i32),
+            NumberType::U64 => decode!(u64),
+            NumberType::I64 => decode!(i64),
+            NumberType::F32 => decode!(f32, format_float),
+            NumberType::F64 => decode!(f64, format_float),
+        };
+        Some(value)
+    }
+}
+
+/// Very large and small magnitudes are shown in scientific notation to keep columns narrow
+fn format_float<T>(value: T) -> String
+where
+    T: Copy + Into<f64> + fmt::Display + fmt::LowerExp,
+{
+    let magnitude = value.into().abs();
+    if magnitude == 0.0 || !magnitude.is_finite() || (1e-4..1e16).contains(&magnitude) {
+        value.to_string()
+    } else {
+        format!("{:e}", value)
+    }
+}
+
+/// Offset given in decimal or with '0x' prefix in hex
+pub fn parse_offset(value: &str) -> std::result::Result<u64, String> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(digits) => u64::from_str_radix(digits, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|_| "expected decimal or 0x prefixed hex offset".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_rows() {
+        let rows = decode_rows(&[0xff, 0x01, 0, 0]);
+        assert_eq!(rows[0], ("u8", "255".to_string(), "255".to_string()));
+        assert_eq!(rows[1], ("i8", "-1".to_string(), "-1".to_string()));
+        assert_eq!(rows[2], ("u16", "511".to_string(), "65281".to_string()));
+        assert_eq!(
+            rows[4],
+            ("u32", "511".to_string(), "4278255616".to_string())
+        );
+        assert_eq!(rows[6], ("u64", "-".to_string(), "-".to_string()));
+        assert_eq!(rows[8].0, "f32");
+    }
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(1.5), "1.5");
+        assert_eq!(format_float(0.0), "0");
+        assert_eq!(format_float(-4.5e300), "-4.5e300");
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("16"), Ok(16));
+        assert_eq!(parse_offset("0x10"), Ok(16));
+        assert!(parse_offset("0xzz").is_err());
+    }
+}
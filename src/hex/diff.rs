@@ -0,0 +1,75 @@
+use std::{
+    fs::File,
+    io::{BufReader, Write},
+    path::Path,
+};
+
+use super::args::HexArgs;
+use super::error::{HexError, Result};
+use super::format::HexRenderer;
+use super::read_full;
+use crate::paths;
+
+/// Dumps only lines where two files differ, first file's line prefixed with '-' and second's
+/// with '+', then a summary of differing byte count
+pub fn diff_files<W: Write>(
+    left_path: &Path,
+    right_path: &Path,
+    args: &HexArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let mut left_reader =
+        BufReader::new(File::open(left_path).map_err(HexError::open_failed(left_path))?);
+    let mut right_reader =
+        BufReader::new(File::open(right_path).map_err(HexError::open_failed(right_path))?);
+    let renderer = HexRenderer::from_args(args);
+    let mut left = vec![0; renderer.bytes_per_line()];
+    let mut right = vec![0; renderer.bytes_per_line()];
+    let mut line = String::new();
+    let mut offset: u64 = 0;
+    let mut differing: u64 = 0;
+    let (mut left_size, mut right_size): (u64, u64) = (0, 0);
+
+    loop {
+        let left_len = read_full(&mut left_reader, &mut left)
+            .map_err(HexError::read_failed(left_path, offset))?;
+        let right_len = read_full(&mut right_reader, &mut right)
+            .map_err(HexError::read_failed(right_path, offset))?;
+        if left_len == 0 && right_len == 0 {
+            break;
+        }
+        left_size += left_len as u64;
+        right_size += right_len as u64;
+
+        let (left, right) = (&left[..left_len], &right[..right_len]);
+        // bytes missing in shorter file count as differing
+        let differs = |i: usize| left.get(i) != right.get(i);
+        let count = (0..left_len.max(right_len)).filter(|i| differs(*i)).count();
+
+        if count > 0 {
+            differing += count as u64;
+            for (prefix, bytes) in [("-", left), ("+", right)] {
+                line.clear();
+                line.push_str(prefix);
+                line.push(' ');
+                renderer.format_marked_line(offset, bytes, differs, &mut line);
+                writeln!(writer, "{}", line)?;
+            }
+        }
+        offset += renderer.bytes_per_line() as u64;
+    }
+
+    writeln!(writer, "{} bytes differ", differing)?;
+    if left_size != right_size {
+        writeln!(
+            writer,
+            "sizes differ: {} has {} bytes, {} has {} bytes",
+            paths::native(left_path),
+            left_size,
+            paths::native(right_path),
+            right_size
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+use super::error::{HexError, Result};
+use super::read_full;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// input bytes encoded at once, a multiple of 3 so only last chunk is padded
+const CHUNK_SIZE: usize = 3 * 1024;
+
+/// bytes per uuencoded line, the maximum line length char can express
+const UU_LINE_BYTES: usize = 45;
+
+/// Writes content as base64 lines of `wrap` characters, a single line when `wrap` is 0
+pub fn write_base64<R: Read, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    wrap: usize,
+    writer: &mut W,
+) -> Result<()> {
+    let mut buffer = [0; CHUNK_SIZE];
+    let mut offset = 0;
+    let mut column = 0;
+
+    loop {
+        let n = read_full(reader, &mut buffer).map_err(HexError::read_failed(file_path, offset))?;
+        if n == 0 {
+            break;
+        }
+        offset += n as u64;
+
+        for c in encode_base64(&buffer[..n]).chars() {
+            if wrap > 0 && column == wrap {
+                writeln!(writer)?;
+                column = 0;
+            }
+            write!(writer, "{}", c)?;
+            column += 1;
+        }
+    }
+    if column > 0 {
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Writes bytes decoded from base64 text, whitespace between characters is ignored
+pub fn decode_base64_file<R: Read, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let mut text = vec![];
+    reader.read_to_end(&mut text).map_err(HexError::read_failed(file_path, 0))?;
+    let bytes = decode_base64(&text).map_err(|reason| HexError::InvalidEncoding {
+        path: file_path.to_path_buf(),
+        reason,
+    })?;
+    writer.write_all(&bytes)?;
+
+    Ok(())
+}
+
+/// Writes content uuencoded between `begin` and `end` lines, named after file
+pub fn write_uuencode<R: Read, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let name = file_path.file_name().map_or("-".into(), |name| name.to_string_lossy());
+    let mut buffer = [0; UU_LINE_BYTES];
+    let mut offset = 0;
+
+    writeln!(writer, "begin 644 {}", name)?;
+    loop {
+        let n = read_full(reader, &mut buffer).map_err(HexError::read_failed(file_path, offset))?;
+        if n == 0 {
+            break;
+        }
+        offset += n as u64;
+        writeln!(writer, "{}", encode_uu_line(&buffer[..n]))?;
+    }
+    writeln!(writer, "`")?;
+    writeln!(writer, "end")?;
+
+    Ok(())
+}
+
+/// Writes bytes decoded from lines between `begin` and `end` lines of uuencoded text
+pub fn decode_uu_file<R: Read, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<()> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(HexError::read_failed(file_path, 0))?;
+    let invalid = |reason: &str| HexError::InvalidEncoding {
+        path: file_path.to_path_buf(),
+        reason: reason.to_string(),
+    };
+
+    let mut lines = text.lines().skip_while(|line| !line.starts_with("begin "));
+    if lines.next().is_none() {
+        return Err(invalid("missing 'begin' line"));
+    }
+    for line in lines {
+        if line == "end" {
+            return Ok(());
+        }
+        let bytes = decode_uu_line(line).map_err(|reason| invalid(&reason))?;
+        writer.write_all(&bytes)?;
+    }
+
+    Err(invalid("missing 'end' line"))
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let value = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (value >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn decode_base64(text: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    let digits: Vec<u8> = text.iter().copied().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(4) {
+        return Err("base64 length isn't a multiple of 4".to_string());
+    }
+
+    let mut bytes = Vec::with_capacity(digits.len() / 4 * 3);
+    let groups = digits.len() / 4;
+    for (i, group) in digits.chunks(4).enumerate() {
+        let padding = group.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i + 1 < groups) {
+            return Err("misplaced '=' padding".to_string());
+        }
+
+        let mut value: u32 = 0;
+        for &c in &group[..4 - padding] {
+            let digit = BASE64_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or_else(|| format!("invalid base64 character '{}'", c as char))?;
+            value = value << 6 | digit as u32;
+        }
+        value <<= 6 * padding;
+
+        bytes.extend_from_slice(&value.to_be_bytes()[1..4 - padding]);
+    }
+
+    Ok(bytes)
+}
+
+/// Length character followed by 4 characters per 3 bytes, 0 is written as '`' instead of space
+fn encode_uu_line(bytes: &[u8]) -> String {
+    let uu_char = |value: u8| {
+        if value == 0 {
+            '`'
+        } else {
+            (value + 32) as char
+        }
+    };
+    let mut line = String::new();
+    line.push(uu_char(bytes.len() as u8));
+
+    for chunk in bytes.chunks(3) {
+        let group = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        line.push(uu_char(group[0] >> 2));
+        line.push(uu_char((group[0] << 4 | group[1] >> 4) & 0x3f));
+        line.push(uu_char((group[1] << 2 | group[2] >> 6) & 0x3f));
+        line.push(uu_char(group[2] & 0x3f));
+    }
+
+    line
+}
+
+fn decode_uu_line(line: &str) -> std::result::Result<Vec<u8>, String> {
+    let chars = line.as_bytes();
+    let Some((&len_char, encoded)) = chars.split_first() else {
+        return Ok(vec![]);
+    };
+    let value = |c: u8| c.wrapping_sub(32) & 0x3f;
+    let len = value(len_char) as usize;
+    if encoded.len() < len.div_ceil(3) * 4 {
+        return Err(format!("uuencoded line is too short: '{}'", line));
+    }
+
+    let mut bytes = Vec::with_capacity(len + 2);
+    for group in encoded.chunks(4).take(len.div_ceil(3)) {
+        let [a, b, c, d] = [group[0], group[1], group[2], group[3]].map(value);
+        bytes.extend_from_slice(&[a << 2 | b >> 4, b << 4 | c >> 2, c << 6 | d]);
+    }
+    bytes.truncate(len);
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+
+        assert_eq!(decode_base64(b"Zm9v\nYmE=").unwrap(), b"fooba");
+        assert!(decode_base64(b"Zm9").is_err());
+        assert!(decode_base64(b"Zg==Zg==").is_err());
+        assert!(decode_base64(b"Z!==").is_err());
+    }
+
+    #[test]
+    fn test_uu_round_trip() {
+        assert_eq!(encode_uu_line(b"Cat"), "#0V%T");
+        let bytes: Vec<u8> = (0..45).collect();
+        assert_eq!(decode_uu_line(&encode_uu_line(&bytes)).unwrap(), bytes);
+        assert_eq!(decode_uu_line("`").unwrap(), b"");
+    }
+}
@@ -0,0 +1,66 @@
+use colored::Colorize;
+
+/// cells of entropy bar, each one is a single terminal column
+pub const BAR_WIDTH: usize = 8;
+
+/// Shannon entropy of bytes relative to the highest one possible for their count, from 0 to 1.
+/// Compressed or encrypted data is close to 1, text is about 0.5 to 0.8.
+pub fn relative_entropy(bytes: &[u8]) -> f64 {
+    let max_entropy = (bytes.len().min(256) as f64).log2();
+    if max_entropy == 0.0 {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+
+    entropy / max_entropy
+}
+
+/// Bar filled in proportion to relative entropy, green for low, yellow for medium and red for
+/// high entropy when colored
+pub fn entropy_bar(bytes: &[u8], color: bool) -> String {
+    let entropy = relative_entropy(bytes);
+    let filled = (entropy * BAR_WIDTH as f64).round() as usize;
+    let bar = format!("{}{}", "█".repeat(filled), "·".repeat(BAR_WIDTH - filled));
+
+    if !color {
+        return bar;
+    }
+    match entropy {
+        e if e < 0.5 => bar.green().to_string(),
+        e if e < 0.9 => bar.yellow().to_string(),
+        _ => bar.red().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_entropy() {
+        assert_eq!(relative_entropy(&[]), 0.0);
+        assert_eq!(relative_entropy(&[7; 16]), 0.0);
+        let distinct: Vec<u8> = (0..16).collect();
+        assert!((relative_entropy(&distinct) - 1.0).abs() < 1e-9);
+        assert!((relative_entropy(&[1, 1, 2, 2]) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_entropy_bar() {
+        assert_eq!(entropy_bar(&[0; 16], false), "········");
+        assert_eq!(entropy_bar(&[1, 1, 2, 2], false), "████····");
+    }
+}
@@ -0,0 +1,129 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::exit::{self, ExitCode};
+
+#[derive(Error, Debug)]
+pub enum HexError {
+    /// failure to write output
+    #[error("{0}")]
+    IoError(#[from] io::Error),
+
+    #[error("cannot open {}: {source}", path.display())]
+    OpenFailed { path: PathBuf, source: io::Error },
+
+    #[error("cannot read {} at offset 0x{offset:x}: {source}", path.display())]
+    ReadFailed {
+        path: PathBuf,
+        offset: u64,
+        source: io::Error,
+    },
+
+    #[error("cannot seek {} to offset 0x{offset:x}: {source}", path.display())]
+    SeekFailed {
+        path: PathBuf,
+        offset: u64,
+        source: io::Error,
+    },
+
+    #[error("cannot write {} at offset 0x{offset:x}: {source}", path.display())]
+    WriteFailed {
+        path: PathBuf,
+        offset: u64,
+        source: io::Error,
+    },
+
+    #[error("cannot skip {skip} bytes of {}, it has only {size} bytes", path.display())]
+    SkipBeyondEnd { path: PathBuf, skip: u64, size: u64 },
+
+    #[error("cannot decode {}: {reason}", path.display())]
+    InvalidEncoding { path: PathBuf, reason: String },
+
+    #[error("{} of the files could not be dumped", .failed.len())]
+    Partial { failed: Vec<HexError> },
+
+    #[error("--diff requires exactly two files")]
+    DiffRequiresTwoFiles,
+
+    #[error("--compare-aligned requires exactly two files")]
+    CompareRequiresTwoFiles,
+
+    #[error("--reverse requires base64 or uu format")]
+    ReverseRequiresEncodedFormat,
+
+    #[error("--patch requires exactly one file")]
+    PatchRequiresSingleFile,
+
+    #[error("patch of {len} bytes at offset {offset} is beyond end of {size} byte file")]
+    PatchOutOfBounds { offset: u64, len: usize, size: u64 },
+}
+
+impl HexError {
+    pub fn open_failed(path: &Path) -> impl FnOnce(io::Error) -> HexError + '_ {
+        move |source| HexError::OpenFailed {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn read_failed(path: &Path, offset: u64) -> impl FnOnce(io::Error) -> HexError + '_ {
+        move |source| HexError::ReadFailed {
+            path: path.to_path_buf(),
+            offset,
+            source,
+        }
+    }
+
+    pub fn seek_failed(path: &Path, offset: u64) -> impl FnOnce(io::Error) -> HexError + '_ {
+        move |source| HexError::SeekFailed {
+            path: path.to_path_buf(),
+            offset,
+            source,
+        }
+    }
+
+    pub fn write_failed(path: &Path, offset: u64) -> impl FnOnce(io::Error) -> HexError + '_ {
+        move |source| HexError::WriteFailed {
+            path: path.to_path_buf(),
+            offset,
+            source,
+        }
+    }
+
+    /// Failure of a single input file, other files can still be dumped
+    pub fn is_file_error(&self) -> bool {
+        matches!(
+            self,
+            HexError::OpenFailed { .. }
+                | HexError::ReadFailed { .. }
+                | HexError::SeekFailed { .. }
+                | HexError::SkipBeyondEnd { .. }
+                | HexError::InvalidEncoding { .. }
+        )
+    }
+}
+
+impl ExitCode for HexError {
+    /// usage error for options that don't fit given files, I/O error for failures to access them
+    fn exit_code(&self) -> i32 {
+        match self {
+            HexError::DiffRequiresTwoFiles
+            | HexError::CompareRequiresTwoFiles
+            | HexError::ReverseRequiresEncodedFormat
+            | HexError::PatchRequiresSingleFile
+            | HexError::PatchOutOfBounds { .. }
+            | HexError::SkipBeyondEnd { .. } => exit::USAGE,
+            HexError::IoError(_)
+            | HexError::OpenFailed { .. }
+            | HexError::ReadFailed { .. }
+            | HexError::SeekFailed { .. }
+            | HexError::WriteFailed { .. }
+            | HexError::InvalidEncoding { .. }
+            | HexError::Partial { .. } => exit::IO,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, HexError>;
@@ -0,0 +1,185 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use super::args::Endian;
+use super::decode::NumberType;
+use super::error::{HexError, Result};
+use super::read_full;
+
+/// Labeled field of a file header
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    name: String,
+    kind: FieldKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldKind {
+    Number(NumberType, Endian),
+    /// raw bytes shown as hex digits
+    Bytes(usize),
+    /// bytes shown as text, up to first 0x00 byte
+    Text(usize),
+}
+
+impl FieldKind {
+    fn width(self) -> usize {
+        match self {
+            FieldKind::Number(ty, _) => ty.width(),
+            FieldKind::Bytes(len) | FieldKind::Text(len) => len,
+        }
+    }
+
+    /// `None` when there are fewer bytes than field width
+    fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            FieldKind::Number(ty, endian) => ty.decode(bytes, endian),
+            FieldKind::Bytes(len) => {
+                let bytes = bytes.get(..len)?;
+                let digits: Vec<String> =
+                    bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+                Some(digits.join(" "))
+            }
+            FieldKind::Text(len) => {
+                let bytes = bytes.get(..len)?;
+                let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(len);
+                Some(format!("{:?}", String::from_utf8_lossy(&bytes[..end])))
+            }
+        }
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = String;
+
+    /// Number type with optional byte order suffix like 'u32be', 'bytesN' or 'strN'
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let length = |digits: &str| match digits.parse() {
+            Ok(0) | Err(_) => Err(format!("invalid length in field type '{}'", s)),
+            Ok(len) => Ok(len),
+        };
+
+        if let Some(digits) = s.strip_prefix("bytes") {
+            return length(digits).map(FieldKind::Bytes);
+        }
+        if let Some(digits) = s.strip_prefix("str") {
+            return length(digits).map(FieldKind::Text);
+        }
+
+        let (name, endian) = match (s.strip_suffix("be"), s.strip_suffix("le")) {
+            (Some(name), _) => (name, Endian::Big),
+            (_, Some(name)) => (name, Endian::Little),
+            _ => (s, Endian::Little),
+        };
+        NumberType::from_name(name)
+            .map(|ty| FieldKind::Number(ty, endian))
+            .ok_or_else(|| {
+                format!(
+                    "unknown field type '{}', expected number type like 'u32' or 'u32be', 'bytesN' or 'strN'",
+                    s
+                )
+            })
+    }
+}
+
+/// Fields laid out one after another from start of file, parsed from `--fields`
+/// like `u32:magic,u16be:version,bytes16:uuid`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldList(pub Vec<Field>);
+
+impl FromStr for FieldList {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.split(',')
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let (kind, name) = item
+                    .split_once(':')
+                    .ok_or_else(|| format!("field '{}' should be like 'TYPE:NAME'", item))?;
+                Ok(Field {
+                    name: name.to_string(),
+                    kind: kind.parse()?,
+                })
+            })
+            .collect::<std::result::Result<_, _>>()
+            .map(FieldList)
+    }
+}
+
+/// Prints decoded fields with their offsets, fields beyond end of file are shown as '-'
+pub fn write_fields<W: Write>(file_path: &Path, fields: &FieldList, writer: &mut W) -> Result<()> {
+    let total: usize = fields.0.iter().map(|field| field.kind.width()).sum();
+    let mut buffer = vec![0; total];
+    let file = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+    let n = read_full(&mut file.take(total as u64), &mut buffer)
+        .map_err(HexError::read_failed(file_path, 0))?;
+    let bytes = &buffer[..n];
+
+    let name_width = fields.0.iter().map(|field| field.name.len()).max().unwrap_or(0);
+    let mut offset = 0;
+    for field in &fields.0 {
+        let value = bytes
+            .get(offset..)
+            .and_then(|rest| field.kind.decode(rest))
+            .unwrap_or_else(|| "-".to_string());
+        writeln!(
+            writer,
+            "{:<name_width$}  [0x{:08x}]  {}",
+            field.name,
+            offset,
+            value,
+            name_width = name_width
+        )?;
+        offset += field.kind.width();
+    }
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields() {
+        let fields: FieldList = "u32:magic,u16be:version,bytes4:id,str8:name".parse().unwrap();
+        let kinds: Vec<FieldKind> = fields.0.iter().map(|field| field.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                FieldKind::Number(NumberType::U32, Endian::Little),
+                FieldKind::Number(NumberType::U16, Endian::Big),
+                FieldKind::Bytes(4),
+                FieldKind::Text(8),
+            ]
+        );
+        assert_eq!(fields.0[0].name, "magic");
+
+        assert!("u32".parse::<FieldList>().is_err());
+        assert!("u24:x".parse::<FieldList>().is_err());
+        assert!("bytes0:x".parse::<FieldList>().is_err());
+    }
+
+    #[test]
+    fn test_decode_field() {
+        assert_eq!(
+            FieldKind::Number(NumberType::U16, Endian::Big).decode(&[1, 2]),
+            Some("258".to_string())
+        );
+        assert_eq!(
+            FieldKind::Bytes(2).decode(&[0xde, 0xad, 0]),
+            Some("de ad".to_string())
+        );
+        assert_eq!(
+            FieldKind::Text(4).decode(b"ab\0c"),
+            Some("\"ab\"".to_string())
+        );
+        assert_eq!(FieldKind::Text(4).decode(b"ab"), None);
+    }
+}
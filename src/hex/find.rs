@@ -0,0 +1,137 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::args::HexArgs;
+use super::error::{HexError, Result};
+use super::format::HexRenderer;
+use super::read_full;
+
+/// lines dumped before and after the line of each match
+const CONTEXT_LINES: u64 = 2;
+
+/// bytes read from file at once while scanning
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Prints offset of every occurrence of pattern with surrounding lines dumped, match highlighted
+pub fn find_in_file<W: Write>(
+    file_path: &Path,
+    pattern: &[u8],
+    args: &HexArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let mut file = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+    let offsets =
+        find_offsets(&mut BufReader::new(&mut file), pattern).map_err(|(offset, source)| {
+            HexError::ReadFailed {
+                path: file_path.to_path_buf(),
+                offset,
+                source,
+            }
+        })?;
+    let renderer = HexRenderer::from_args(args);
+    let bytes_per_line = renderer.bytes_per_line() as u64;
+    let pattern_len = pattern.len() as u64;
+    let mut buffer = vec![0; renderer.bytes_per_line()];
+    let mut line = String::new();
+
+    for (i, &found) in offsets.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "match at 0x{:08x} ({})", found, found)?;
+
+        let first_line = (found / bytes_per_line).saturating_sub(CONTEXT_LINES);
+        let last_line = (found + pattern_len - 1) / bytes_per_line + CONTEXT_LINES;
+        let line_offset = first_line * bytes_per_line;
+        file.seek(SeekFrom::Start(line_offset))
+            .map_err(HexError::seek_failed(file_path, line_offset))?;
+
+        for line_index in first_line..=last_line {
+            let offset = line_index * bytes_per_line;
+            let n = read_full(&mut file, &mut buffer)
+                .map_err(HexError::read_failed(file_path, offset))?;
+            if n == 0 {
+                break;
+            }
+
+            let is_match = |i: usize| (found..found + pattern_len).contains(&(offset + i as u64));
+            line.clear();
+            renderer.format_marked_line(offset, &buffer[..n], is_match, &mut line);
+            writeln!(writer, "{}", line)?;
+        }
+    }
+
+    if !offsets.is_empty() {
+        writeln!(writer)?;
+    }
+    writeln!(writer, "{} matches", offsets.len())?;
+
+    Ok(())
+}
+
+/// Start offsets of all, possibly overlapping, occurrences of non-empty pattern
+/// fails with offset of failed read
+fn find_offsets<R: Read>(
+    reader: &mut R,
+    pattern: &[u8],
+) -> std::result::Result<Vec<u64>, (u64, io::Error)> {
+    let mut offsets = vec![];
+    if pattern.is_empty() {
+        return Ok(offsets);
+    }
+    // unmatched tail of previous chunk followed by current chunk
+    let mut window: Vec<u8> = vec![];
+    let mut window_start: u64 = 0;
+    let mut chunk = vec![0; CHUNK_SIZE];
+
+    loop {
+        let read_offset = window_start + window.len() as u64;
+        let n = read_full(reader, &mut chunk).map_err(|e| (read_offset, e))?;
+        if n == 0 {
+            break;
+        }
+        window.extend_from_slice(&chunk[..n]);
+
+        for (i, candidate) in window.windows(pattern.len()).enumerate() {
+            if candidate == pattern {
+                offsets.push(window_start + i as u64);
+            }
+        }
+
+        // match may span chunks, keep bytes that can still start one
+        let keep = (pattern.len() - 1).min(window.len());
+        let consumed = window.len() - keep;
+        window.drain(..consumed);
+        window_start += consumed as u64;
+    }
+
+    Ok(offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_offsets() {
+        let mut input: &[u8] = b"abcabcab";
+        assert_eq!(find_offsets(&mut input, b"ab").unwrap(), vec![0, 3, 6]);
+
+        let mut input: &[u8] = b"aaaa";
+        assert_eq!(find_offsets(&mut input, b"aa").unwrap(), vec![0, 1, 2]);
+        assert_eq!(find_offsets(&mut input, b"").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_find_offsets_across_chunks() {
+        let mut data = vec![0u8; CHUNK_SIZE + 8];
+        data[CHUNK_SIZE - 1..CHUNK_SIZE + 2].copy_from_slice(b"xyz");
+        assert_eq!(
+            find_offsets(&mut data.as_slice(), b"xyz").unwrap(),
+            vec![CHUNK_SIZE as u64 - 1]
+        );
+    }
+}
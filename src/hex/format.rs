@@ -0,0 +1,519 @@
+use std::io::{self, Write};
+
+use colored::{Color, Colorize};
+use terminal_size::{Width, terminal_size};
+use unicode_width::UnicodeWidthChar;
+
+use super::args::{Columns, DumpStyleArgs};
+use super::args::{Endian, HexArgs, HexFormat, Highlight, OffsetBase, TextEncoding};
+use super::entropy::{BAR_WIDTH as ENTROPY_BAR_WIDTH, entropy_bar};
+
+/// width fitted by `--columns auto` when output isn't a terminal
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// Renders dump lines, shared by `hex` and `view --format hex`
+pub struct HexRenderer {
+    bytes_per_line: usize,
+    group_size: usize,
+    endian: Option<Endian>,
+    offset_base: OffsetBase,
+    /// shown for 0x00 and 0xff bytes, `None` when disabled
+    placeholders: Option<(String, String)>,
+    plain: bool,
+    /// bytes shown as 3 octal digits like `od -b` instead of 2 hex digits
+    octal: bool,
+    text_encoding: TextEncoding,
+    highlights: Vec<Highlight>,
+    /// entropy bar after text column
+    entropy: bool,
+    color: bool,
+}
+
+impl HexRenderer {
+    /// Renderer of dump lines without highlighting or entropy, as `view --format hex` shows them
+    pub fn new(columns: Columns, group_size: usize, style: &DumpStyleArgs, color: bool) -> Self {
+        let mut renderer = Self::with_style(group_size, style, color);
+        renderer.set_columns(columns);
+        renderer
+    }
+
+    pub fn from_args(args: &HexArgs) -> Self {
+        let mut renderer = Self::with_style(args.group_size, &args.style, args.color.enabled());
+        let octal = args.format == HexFormat::Octal;
+        // words are hex only
+        renderer.endian = renderer.endian.filter(|_| !octal);
+        renderer.plain = args.plain;
+        renderer.octal = octal;
+        renderer.highlights = args.highlight.clone();
+        renderer.entropy = args.entropy;
+        // fitting to terminal width depends on the rest of layout
+        renderer.set_columns(args.bytes_per_line);
+        renderer
+    }
+
+    /// Renderer of given style, bytes per line are set by `set_columns` once layout is complete
+    fn with_style(group_size: usize, style: &DumpStyleArgs, color: bool) -> Self {
+        HexRenderer {
+            bytes_per_line: 0,
+            group_size,
+            endian: style.endian,
+            offset_base: style.offset_base,
+            placeholders: (!style.no_placeholders)
+                .then(|| (style.zero_placeholder.clone(), style.ff_placeholder.clone())),
+            plain: false,
+            octal: false,
+            text_encoding: style.text_encoding,
+            highlights: vec![],
+            entropy: false,
+            color,
+        }
+    }
+
+    fn set_columns(&mut self, columns: Columns) {
+        self.bytes_per_line = match columns {
+            Columns::Count(count) => count,
+            Columns::Auto => {
+                let width =
+                    terminal_size().map_or(DEFAULT_TERMINAL_WIDTH, |(Width(w), _)| w as usize);
+                self.fit_width(width)
+            }
+        };
+    }
+
+    pub fn bytes_per_line(&self) -> usize {
+        self.bytes_per_line
+    }
+
+    /// Columns taken by digits of a byte
+    fn cell_width(&self) -> usize {
+        if self.octal { 3 } else { 2 }
+    }
+
+    /// Digits of a byte in hex or octal
+    fn byte_digits(&self, byte: u8) -> String {
+        if self.octal {
+            format!("{:03o}", byte)
+        } else {
+            format!("{:02x}", byte)
+        }
+    }
+
+    /// Most bytes per line, in whole groups, whose dump line fits in `width` columns
+    fn fit_width(&self, width: usize) -> usize {
+        let group_size = self.group_size;
+        let cell_width = self.cell_width();
+        if self.plain {
+            return (width / cell_width).max(1);
+        }
+
+        let offset_width = match self.offset_base {
+            OffsetBase::Hex | OffsetBase::Dec => "[0x00000000] ".len(),
+            OffsetBase::Oct => "[0o00000000000] ".len(),
+            OffsetBase::None => 0,
+        };
+        // digits, spaces and text column of one group, and whether last group of line
+        // saves the extra space separating groups
+        let (group_width, last_saving) = match self.endian {
+            Some(_) => (3 * group_size + 1, 0),
+            None if group_size > 1 => ((cell_width + 2) * group_size + 1, 1),
+            None => (cell_width + 2, 0),
+        };
+        let entropy_width = if self.entropy {
+            2 + ENTROPY_BAR_WIDTH
+        } else {
+            0
+        };
+        let available = width.saturating_sub(offset_width + 2 + entropy_width) + last_saving;
+
+        (available / group_width).max(1) * group_size
+    }
+
+    /// Appends dump line of bytes starting at `offset` to `line`, without line terminator
+    pub fn format_line(&self, offset: u64, bytes: &[u8], line: &mut String) {
+        self.format_marked_line(offset, bytes, |_| false, line);
+    }
+
+    /// Like `format_line`, bytes at indexes for which `marked` is true are highlighted
+    pub fn format_marked_line<F>(&self, offset: u64, bytes: &[u8], marked: F, line: &mut String)
+    where
+        F: Fn(usize) -> bool,
+    {
+        if self.plain {
+            for byte in bytes {
+                line.push_str(&self.byte_digits(*byte));
+            }
+            return;
+        }
+
+        match self.offset_base {
+            OffsetBase::Hex => line.push_str(&format!("[0x{:08x}] ", offset)),
+            OffsetBase::Dec => line.push_str(&format!("[{:010}] ", offset)),
+            OffsetBase::Oct => line.push_str(&format!("[0o{:011o}] ", offset)),
+            OffsetBase::None => {}
+        }
+        let style = |i: usize| {
+            if marked(i) {
+                Some(CellStyle::Marked)
+            } else {
+                self.highlight_color(offset + i as u64).map(CellStyle::Highlight)
+            }
+        };
+        match self.endian {
+            Some(endian) => self.format_words(bytes, endian, &style, line),
+            None => self.format_bytes(bytes, &style, line),
+        }
+        line.push_str("  ");
+        match self.text_encoding {
+            TextEncoding::Ascii => self.format_text(bytes, &style, line),
+            TextEncoding::Utf8 => self.format_utf8_text(bytes, &style, line),
+        }
+
+        if self.entropy {
+            // short last line keeps bar aligned with the ones above
+            line.extend(std::iter::repeat_n(
+                ' ',
+                self.bytes_per_line.saturating_sub(bytes.len()),
+            ));
+            line.push_str("  ");
+            line.push_str(&entropy_bar(bytes, self.color));
+        }
+    }
+
+    /// Color of first `--highlight` range containing offset
+    fn highlight_color(&self, offset: u64) -> Option<Color> {
+        self.highlights
+            .iter()
+            .find(|highlight| (highlight.start..=highlight.end).contains(&offset))
+            .map(|highlight| highlight.color)
+    }
+
+    fn push_cell(&self, cell: &str, style: Option<CellStyle>, column: &mut String) {
+        match style {
+            Some(CellStyle::Marked) if self.color => {
+                column.push_str(&cell.red().bold().to_string())
+            }
+            Some(CellStyle::Highlight(color)) if self.color => {
+                column.push_str(&cell.on_color(color).to_string())
+            }
+            _ => column.push_str(cell),
+        }
+    }
+
+    /// Hex or octal column of a line, an extra space separates groups, short last line is padded
+    fn format_bytes(
+        &self,
+        bytes: &[u8],
+        style: &impl Fn(usize) -> Option<CellStyle>,
+        column: &mut String,
+    ) {
+        let width = self.cell_width();
+        for i in 0..self.bytes_per_line {
+            match (bytes.get(i), &self.placeholders) {
+                (Some(0x00), Some((zero, _))) => {
+                    self.push_cell(&format!("{:<width$}", zero), style(i), column)
+                }
+                (Some(0xff), Some((_, ff))) => {
+                    self.push_cell(&format!("{:<width$}", ff), style(i), column)
+                }
+                (Some(byte), _) => self.push_cell(&self.byte_digits(*byte), style(i), column),
+                (None, _) => column.extend(std::iter::repeat_n(' ', width)),
+            }
+            column.push(' ');
+            if self.group_size > 1 && (i + 1) % self.group_size == 0 && i + 1 < self.bytes_per_line
+            {
+                column.push(' ');
+            }
+        }
+    }
+
+    /// Hex column of a line with every group shown as a single word in given byte order
+    fn format_words(
+        &self,
+        bytes: &[u8],
+        endian: Endian,
+        style: &impl Fn(usize) -> Option<CellStyle>,
+        column: &mut String,
+    ) {
+        let group_size = self.group_size;
+
+        for start in (0..self.bytes_per_line).step_by(group_size) {
+            let group = bytes.get(start..bytes.len().min(start + group_size)).unwrap_or(&[]);
+            let digits: String = match endian {
+                Endian::Little => group.iter().rev().map(|byte| format!("{:02x}", byte)).collect(),
+                Endian::Big => group.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            };
+            // partial word is aligned like its missing bytes were the high order ones
+            let width = group_size * 2;
+            let cell = match endian {
+                Endian::Little => format!("{:>width$}", digits),
+                Endian::Big => format!("{:<width$}", digits),
+            };
+            let group_style = (start..start + group.len()).find_map(style);
+            self.push_cell(&cell, group_style, column);
+            column.push(' ');
+        }
+    }
+
+    /// Printable ASCII characters of bytes, other bytes are shown as '.'
+    fn format_text(
+        &self,
+        bytes: &[u8],
+        style: &impl Fn(usize) -> Option<CellStyle>,
+        column: &mut String,
+    ) {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.push_cell(ascii_cell(*byte).encode_utf8(&mut [0; 4]), style(i), column);
+        }
+    }
+
+    /// Like `format_text`, but a multibyte UTF-8 character complete in line is shown in place
+    /// of its first byte, padded with spaces to take one column per byte
+    fn format_utf8_text(
+        &self,
+        bytes: &[u8],
+        style: &impl Fn(usize) -> Option<CellStyle>,
+        column: &mut String,
+    ) {
+        let mut i = 0;
+        while i < bytes.len() {
+            if let Some((c, len)) = decode_utf8_char(&bytes[i..])
+                && let Some(width @ 1..) = c.width()
+                && width <= len
+            {
+                let mut cell = c.to_string();
+                cell.extend(std::iter::repeat_n(' ', len - width));
+                self.push_cell(&cell, (i..i + len).find_map(style), column);
+                i += len;
+            } else {
+                self.push_cell(
+                    ascii_cell(bytes[i]).encode_utf8(&mut [0; 4]),
+                    style(i),
+                    column,
+                );
+                i += 1;
+            }
+        }
+    }
+}
+
+/// How a cell of hex or text column is colored
+#[derive(Clone, Copy)]
+enum CellStyle {
+    /// match of `--find` or difference of `--diff`
+    Marked,
+    /// byte inside a `--highlight` range
+    Highlight(Color),
+}
+
+/// Printable ASCII character of byte, other bytes are shown as '.'
+fn ascii_cell(byte: u8) -> char {
+    let c = byte as char;
+    if c.is_ascii_graphic() || c == ' ' {
+        c
+    } else {
+        '.'
+    }
+}
+
+/// Multibyte character at start of bytes with its encoded length
+fn decode_utf8_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let len = match bytes.first()? {
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return None,
+    };
+    let text = std::str::from_utf8(bytes.get(..len)?).ok()?;
+    text.chars().next().map(|c| (c, len))
+}
+
+/// Writer dumping bytes written to it as complete lines
+pub struct HexWriter<W: Write> {
+    renderer: HexRenderer,
+    inner: W,
+    /// bytes of line not complete yet
+    pending: Vec<u8>,
+    offset: u64,
+    line: String,
+}
+
+impl<W: Write> HexWriter<W> {
+    /// Offsets start from 0
+    pub fn new(renderer: HexRenderer, inner: W) -> Self {
+        Self::with_offset(renderer, inner, 0)
+    }
+
+    /// Offsets start from `offset`, for bytes from the middle of input
+    pub fn with_offset(renderer: HexRenderer, inner: W, offset: u64) -> Self {
+        HexWriter {
+            pending: Vec::with_capacity(renderer.bytes_per_line),
+            renderer,
+            inner,
+            offset,
+            line: String::new(),
+        }
+    }
+
+    /// Continues dump at `offset`, current short line is written first
+    pub fn jump_to(&mut self, offset: u64) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.write_dump_line(&pending)?;
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes last short line, called after all bytes are written
+    pub fn finish(mut self) -> io::Result<W> {
+        self.jump_to(self.offset)?;
+        Ok(self.inner)
+    }
+
+    fn write_dump_line(&mut self, bytes: &[u8]) -> io::Result<()> {
+        // reuse single String buffer for every line
+        self.line.clear();
+        self.renderer.format_line(self.offset, bytes, &mut self.line);
+        writeln!(self.inner, "{}", self.line)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for HexWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes_per_line = self.renderer.bytes_per_line;
+        let mut rest = buf;
+
+        // complete lines are dumped straight from `buf` once pending line is filled up
+        if !self.pending.is_empty() {
+            let n = rest.len().min(bytes_per_line - self.pending.len());
+            self.pending.extend_from_slice(&rest[..n]);
+            rest = &rest[n..];
+            if self.pending.len() < bytes_per_line {
+                return Ok(buf.len());
+            }
+            let mut pending = std::mem::take(&mut self.pending);
+            self.write_dump_line(&pending)?;
+            pending.clear();
+            self.pending = pending;
+        }
+        while rest.len() >= bytes_per_line {
+            let (line, remaining) = rest.split_at(bytes_per_line);
+            self.write_dump_line(line)?;
+            rest = remaining;
+        }
+        self.pending.extend_from_slice(rest);
+
+        Ok(buf.len())
+    }
+
+    /// Partial line is held back until it's complete or `finish` is called
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renderer(bytes_per_line: usize, group_size: usize, endian: Option<Endian>) -> HexRenderer {
+        HexRenderer {
+            bytes_per_line,
+            group_size,
+            endian,
+            offset_base: OffsetBase::Hex,
+            placeholders: Some((".".to_string(), "##".to_string())),
+            plain: false,
+            octal: false,
+            text_encoding: TextEncoding::Ascii,
+            highlights: vec![],
+            entropy: false,
+            color: false,
+        }
+    }
+
+    #[test]
+    fn test_format_bytes_groups() {
+        let mut column = String::new();
+        renderer(4, 2, None).format_bytes(&[1, 2, 3], &|_| None, &mut column);
+        assert_eq!(column, "01 02  03    ");
+
+        let mut column = String::new();
+        renderer(2, 1, None).format_bytes(&[0, 0xff], &|_| None, &mut column);
+        assert_eq!(column, ".  ## ");
+    }
+
+    #[test]
+    fn test_format_words() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05];
+
+        let mut column = String::new();
+        renderer(8, 4, None).format_words(&bytes, Endian::Big, &|_| None, &mut column);
+        assert_eq!(column, "01020304 05       ");
+
+        let mut column = String::new();
+        renderer(8, 4, None).format_words(&bytes, Endian::Little, &|_| None, &mut column);
+        assert_eq!(column, "04030201       05 ");
+    }
+
+    #[test]
+    fn test_format_octal() {
+        let mut renderer = renderer(4, 2, None);
+        renderer.octal = true;
+        let mut line = String::new();
+        renderer.format_line(0, &[8, 0, 0o377], &mut line);
+        assert_eq!(line, "[0x00000000] 010 .    ##        ...");
+        assert_eq!(renderer.fit_width(13 + 2 + 5 * 4 + 1 + 4), 4);
+    }
+
+    #[test]
+    fn test_fit_width() {
+        // 13 offset + 16 * 3 hex + 2 + 16 text
+        assert_eq!(renderer(0, 1, None).fit_width(79), 16);
+        assert_eq!(renderer(0, 1, None).fit_width(78), 15);
+        assert_eq!(renderer(0, 4, None).fit_width(80), 12);
+        assert_eq!(renderer(0, 4, None).fit_width(10), 4);
+    }
+
+    #[test]
+    fn test_format_line() {
+        let mut line = String::new();
+        renderer(4, 1, None).format_line(0x10, b"ab\n", &mut line);
+        assert_eq!(line, "[0x00000010] 61 62 0a      ab.");
+    }
+
+    #[test]
+    fn test_format_utf8_text() {
+        let mut renderer = renderer(16, 1, None);
+        renderer.text_encoding = TextEncoding::Utf8;
+
+        let mut column = String::new();
+        renderer.format_utf8_text("aé中\n".as_bytes(), &|_| None, &mut column);
+        assert_eq!(column, "aé 中 .");
+
+        // character cut by end of line
+        let mut column = String::new();
+        renderer.format_utf8_text(&"中".as_bytes()[..2], &|_| None, &mut column);
+        assert_eq!(column, "..");
+    }
+
+    #[test]
+    fn test_hex_writer_splits_lines() {
+        let mut writer = HexWriter::new(renderer(4, 1, None), vec![]);
+        writer.write_all(b"ab").unwrap();
+        writer.write_all(b"cdefg").unwrap();
+        let output = writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "[0x00000000] 61 62 63 64   abcd\n\
+             [0x00000004] 65 66 67      efg\n"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+    time::Instant,
+};
+
+use memmap2::Mmap;
+
+mod args;
+mod c_array;
+mod checksum;
+mod compare;
+mod decode;
+mod diff;
+mod encode;
+mod entropy;
+mod error;
+mod fields;
+mod find;
+mod format;
+mod pager;
+mod patch;
+mod progress;
+mod select;
+
+pub use args::{
+    BYTES_PER_LINE, Columns, DumpStyleArgs, Endian, HexArgs, HexFormat, group_size_parser,
+    parse_columns,
+};
+pub use error::{HexError, Result};
+pub use format::{HexRenderer, HexWriter};
+use progress::Progress;
+
+use crate::cancel;
+use crate::paths;
+use crate::report;
+
+/// files at least this large are memory mapped instead of read in chunks
+const MMAP_THRESHOLD: u64 = 1 << 20;
+
+/// bytes read at once from files that aren't memory mapped
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Dumps files to stdout
+pub fn view_hex(args: HexArgs) -> Result<()> {
+    // colored checks terminal on its own, global --color choice has to override it
+    colored::control::set_override(args.color.enabled());
+    let mut writer = BufWriter::new(io::stdout().lock());
+
+    if pager::should_page(&args) {
+        pager::page_file(&args.file_paths[0], &args, &mut writer)
+    } else {
+        view_hex_to(&args, &mut writer)
+    }
+}
+
+/// Dumps files to given writer
+pub fn view_hex_to<W: Write>(args: &HexArgs, writer: &mut W) -> Result<()> {
+    let start = Instant::now();
+    let result = dump_files(args, writer);
+    // dumps of succeeded files are flushed even when others failed
+    writer.flush()?;
+    log::info!(
+        "dumped {} files in {:?}",
+        args.file_paths.len(),
+        start.elapsed()
+    );
+    result
+}
+
+fn dump_files<W: Write>(args: &HexArgs, writer: &mut W) -> Result<()> {
+    if args.diff {
+        let [left, right] = args.file_paths.as_slice() else {
+            return Err(HexError::DiffRequiresTwoFiles);
+        };
+        return diff::diff_files(left, right, args, writer);
+    }
+
+    if args.compare_aligned {
+        let [left, right] = args.file_paths.as_slice() else {
+            return Err(HexError::CompareRequiresTwoFiles);
+        };
+        return compare::compare_aligned(left, right, args, writer);
+    }
+
+    if args.reverse && !matches!(args.format, HexFormat::Base64 | HexFormat::Uu) {
+        return Err(HexError::ReverseRequiresEncodedFormat);
+    }
+
+    if !args.patch.is_empty() {
+        let [file_path] = args.file_paths.as_slice() else {
+            return Err(HexError::PatchRequiresSingleFile);
+        };
+        return patch::patch_file(file_path, args, writer);
+    }
+
+    if let [file_path] = args.file_paths.as_slice() {
+        return dump_file(file_path, args, writer);
+    }
+
+    let mut failed = vec![];
+    for (i, file_path) in args.file_paths.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        // header would make generated source code invalid
+        if args.format.is_dump() {
+            writeln!(writer, "==> {} <==", paths::native(file_path))?;
+        }
+
+        // continue with remaining files, failure is reported when all are dumped
+        match dump_file(file_path, args, writer) {
+            Ok(()) => {}
+            Err(e) if e.is_file_error() => {
+                writer.flush()?;
+                report::file_error("hex", &e);
+                failed.push(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(HexError::Partial { failed })
+    }
+}
+
+fn dump_file<W: Write>(file_path: &Path, args: &HexArgs, writer: &mut W) -> Result<()> {
+    if let Some(pattern) = args.find_pattern() {
+        return find::find_in_file(file_path, pattern, args, writer);
+    }
+
+    let mut reader = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+
+    match args.format {
+        HexFormat::Dump | HexFormat::Octal => {
+            if let Some(fields) = &args.fields {
+                fields::write_fields(file_path, fields, writer)?;
+            }
+            match &args.bytes {
+                Some(spec) => {
+                    let total = reader.metadata().map_err(HexError::open_failed(file_path))?.len();
+                    select::dump_selected_bytes(
+                        file_path,
+                        &mut BufReader::new(reader),
+                        total,
+                        spec,
+                        args,
+                        writer,
+                    )?;
+                }
+                None => dump_content(file_path, reader, args, writer)?,
+            }
+            if let Some(offset) = args.decode {
+                decode::write_decode_panel(file_path, offset, writer)?;
+            }
+            if args.checksum.is_some() || args.block_checksum.is_some() {
+                checksum::write_checksums(file_path, args, writer)?;
+            }
+            Ok(())
+        }
+        HexFormat::CArray => {
+            skip_bytes(file_path, &mut reader, args.skip)?;
+            c_array::write_c_array(file_path, &mut reader, writer)
+        }
+        HexFormat::Base64 if args.reverse => {
+            encode::decode_base64_file(file_path, &mut reader, writer)
+        }
+        HexFormat::Base64 => {
+            skip_bytes(file_path, &mut reader, args.skip)?;
+            encode::write_base64(file_path, &mut reader, args.wrap, writer)
+        }
+        HexFormat::Uu if args.reverse => encode::decode_uu_file(file_path, &mut reader, writer),
+        HexFormat::Uu => {
+            skip_bytes(file_path, &mut reader, args.skip)?;
+            encode::write_uuencode(file_path, &mut reader, writer)
+        }
+    }
+}
+
+/// Dumps content after skipped bytes, large regular files are memory mapped
+fn dump_content<W: Write>(
+    file_path: &Path,
+    mut file: File,
+    args: &HexArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let metadata = file.metadata().map_err(HexError::open_failed(file_path))?;
+    let renderer = HexRenderer::from_args(args);
+    let mut hex_writer = HexWriter::with_offset(renderer, writer, args.skip);
+    let total = metadata.is_file().then(|| metadata.len().saturating_sub(args.skip));
+    let mut progress = Progress::start(total, args.quiet_progress);
+
+    let mapped = metadata.is_file() && metadata.len() >= MMAP_THRESHOLD;
+    log::debug!(
+        "dumping {}, {} bytes, {}",
+        file_path.display(),
+        metadata.len(),
+        if mapped {
+            "memory mapped"
+        } else {
+            "read in chunks"
+        }
+    );
+    if mapped {
+        check_skip(file_path, args.skip, metadata.len())?;
+        // SAFETY: mapping is only read, a file truncated by another process meanwhile
+        // may fault like it would for any other tool mapping files
+        let mmap = unsafe { Mmap::map(&file) }.map_err(HexError::read_failed(file_path, 0))?;
+        for chunk in mmap[args.skip as usize..].chunks(READ_CHUNK_SIZE) {
+            if cancel::is_cancelled() {
+                break;
+            }
+            hex_writer.write_all(chunk)?;
+            if let Some(progress) = &mut progress {
+                progress.advance(chunk.len() as u64);
+            }
+        }
+    } else {
+        skip_bytes(file_path, &mut file, args.skip)?;
+        let mut offset = args.skip;
+        let mut buffer = vec![0; READ_CHUNK_SIZE];
+        // partial dump is still finished with its last line when cancelled
+        while !cancel::is_cancelled() {
+            let n = read_full(&mut file, &mut buffer)
+                .map_err(HexError::read_failed(file_path, offset))?;
+            if n == 0 {
+                break;
+            }
+            hex_writer.write_all(&buffer[..n])?;
+            offset += n as u64;
+            if let Some(progress) = &mut progress {
+                progress.advance(n as u64);
+            }
+        }
+    }
+    hex_writer.finish()?;
+    cancel::check()?;
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    Ok(())
+}
+
+/// Seeks regular files, pipes and devices can't seek so skipped bytes are read and dropped
+fn skip_bytes(file_path: &Path, file: &mut File, skip: u64) -> Result<()> {
+    if skip == 0 {
+        return Ok(());
+    }
+
+    let metadata = file.metadata().map_err(HexError::open_failed(file_path))?;
+    if metadata.is_file() {
+        check_skip(file_path, skip, metadata.len())?;
+        file.seek(SeekFrom::Start(skip))
+            .map_err(HexError::seek_failed(file_path, skip))?;
+    } else {
+        io::copy(&mut file.take(skip), &mut io::sink())
+            .map_err(HexError::read_failed(file_path, 0))?;
+    }
+    Ok(())
+}
+
+fn check_skip(file_path: &Path, skip: u64, size: u64) -> Result<()> {
+    if skip > size {
+        return Err(HexError::SkipBeyondEnd {
+            path: file_path.to_path_buf(),
+            skip,
+            size,
+        });
+    }
+    Ok(())
+}
+
+/// Fills buffer unless end of input is reached, so every line except last one is complete
+pub fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_view_hex_to_writer() {
+        let file_path = std::env::temp_dir().join("kf_view_hex_to_writer.bin");
+        fs::write(&file_path, b"0123456789abcdefXY").unwrap();
+
+        let args = HexArgs::parse_from(["hex", file_path.to_str().unwrap()]);
+        let mut output = vec![];
+        view_hex_to(&args, &mut output).unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "[0x00000000] 30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 66   0123456789abcdef\n\
+             [0x00000010] 58 59                                             XY\n"
+        );
+    }
+}
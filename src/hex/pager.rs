@@ -0,0 +1,119 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, IsTerminal, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use terminal_size::{Height, terminal_size};
+
+use super::args::HexArgs;
+use super::decode::parse_offset;
+use super::error::{HexError, Result};
+use super::format::HexRenderer;
+use super::read_full;
+use crate::view::Paging;
+
+/// Whether dump of single file is paged, only plain dumps of regular files with keyboard
+/// input available are. `Auto` pages output longer than terminal height.
+pub fn should_page(args: &HexArgs) -> bool {
+    let [file_path] = args.file_paths.as_slice() else {
+        return false;
+    };
+    let plain_dump = args.format.is_dump()
+        && !args.diff
+        && !args.compare_aligned
+        && args.find_pattern().is_none()
+        && args.patch.is_empty()
+        && args.bytes.is_none()
+        && args.fields.is_none()
+        && args.decode.is_none()
+        && args.checksum.is_none()
+        && args.block_checksum.is_none();
+    if !plain_dump || !io::stdin().is_terminal() {
+        return false;
+    }
+
+    match args.paging {
+        Paging::Never => false,
+        Paging::Always => true,
+        Paging::Auto => {
+            let Some((_, Height(rows))) = terminal_size() else {
+                return false;
+            };
+            let bytes_per_line = HexRenderer::from_args(args).bytes_per_line() as u64;
+            let size = file_path.metadata().map_or(0, |metadata| metadata.len());
+            io::stdout().is_terminal()
+                && size.saturating_sub(args.skip) > rows as u64 * bytes_per_line
+        }
+    }
+}
+
+/// Dumps file screen by screen. Commands are read line by line from terminal in its normal mode,
+/// so each is confirmed with enter: space for next screen, empty line for next line,
+/// 'g OFFSET' to jump and 'q' to quit
+pub fn page_file<W: Write>(file_path: &Path, args: &HexArgs, writer: &mut W) -> Result<()> {
+    let mut file = File::open(file_path).map_err(HexError::open_failed(file_path))?;
+    let size = file.metadata().map_err(HexError::open_failed(file_path))?.len();
+    let renderer = HexRenderer::from_args(args);
+    let bytes_per_line = renderer.bytes_per_line() as u64;
+    // last row is taken by prompt
+    let rows = terminal_size().map_or(24, |(_, Height(rows))| rows as u64).max(2) - 1;
+
+    let mut offset = args.skip;
+    let mut lines = rows;
+    let mut buffer = vec![0; bytes_per_line as usize];
+    let mut line = String::new();
+    let mut stdin = io::stdin().lock();
+    let mut command = String::new();
+
+    loop {
+        file.seek(SeekFrom::Start(offset))
+            .map_err(HexError::seek_failed(file_path, offset))?;
+        for _ in 0..lines {
+            let n = read_full(&mut file, &mut buffer)
+                .map_err(HexError::read_failed(file_path, offset))?;
+            if n == 0 {
+                break;
+            }
+            line.clear();
+            renderer.format_line(offset, &buffer[..n], &mut line);
+            writeln!(writer, "{}", line)?;
+            offset += n as u64;
+        }
+        writer.flush()?;
+        if offset >= size {
+            return Ok(());
+        }
+
+        eprint!(
+            "-- 0x{:08x} / 0x{:08x} ({}%) -- type and press enter: space (page), nothing (line), g OFFSET (jump), q (quit) ",
+            offset,
+            size,
+            offset * 100 / size.max(1)
+        );
+        command.clear();
+        if stdin.read_line(&mut command)? == 0 {
+            return Ok(());
+        }
+        // replace prompt and echoed command with following output
+        eprint!("\x1b[1A\x1b[2K");
+
+        let command = command.trim_end_matches(['\r', '\n']);
+        lines = match command.trim() {
+            "q" => return Ok(()),
+            "" if command.is_empty() => 1,
+            "" => rows,
+            jump => match jump.strip_prefix('g').map(|target| parse_offset(target.trim())) {
+                Some(Ok(target)) => {
+                    // keep lines aligned like the rest of dump
+                    offset = target.min(size.saturating_sub(1)) / bytes_per_line * bytes_per_line;
+                    rows
+                }
+                _ => {
+                    eprintln!("unknown command '{}'", jump);
+                    0
+                }
+            },
+        };
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fs::OpenOptions,
+    io::{Seek, SeekFrom, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use super::args::{HexArgs, parse_hex_bytes};
+use super::decode::parse_offset;
+use super::error::{HexError, Result};
+use super::format::HexRenderer;
+use super::read_full;
+
+/// Bytes written over file content at offset, parsed from `--patch` like `0x10=dead beef`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    offset: u64,
+    bytes: Vec<u8>,
+}
+
+impl FromStr for Patch {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (offset, bytes) = s
+            .split_once('=')
+            .ok_or_else(|| "patch should be like 'OFFSET=HEXBYTES'".to_string())?;
+        Ok(Patch {
+            offset: parse_offset(offset.trim())?,
+            bytes: parse_hex_bytes(bytes)?,
+        })
+    }
+}
+
+/// Offset after last patched byte, patch must lie within file of `size` bytes
+fn patch_end(patch: &Patch, size: u64) -> Result<u64> {
+    patch
+        .offset
+        .checked_add(patch.bytes.len() as u64)
+        .filter(|&end| end <= size)
+        .ok_or(HexError::PatchOutOfBounds {
+            offset: patch.offset,
+            len: patch.bytes.len(),
+            size,
+        })
+}
+
+/// Overwrites bytes in place, patches can't extend file. Changed lines are dumped before
+/// ('-') and after ('+') patching with `--dry-run`, which leaves file untouched.
+pub fn patch_file<W: Write>(file_path: &Path, args: &HexArgs, writer: &mut W) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(!args.dry_run)
+        .open(file_path)
+        .map_err(HexError::open_failed(file_path))?;
+    let size = file.metadata().map_err(HexError::open_failed(file_path))?.len();
+
+    // every patch is checked before any of them is written
+    for patch in &args.patch {
+        patch_end(patch, size)?;
+    }
+
+    let renderer = HexRenderer::from_args(args);
+    let bytes_per_line = renderer.bytes_per_line() as u64;
+    let mut line = String::new();
+
+    for patch in &args.patch {
+        let end = patch_end(patch, size)?;
+        if !args.dry_run {
+            file.seek(SeekFrom::Start(patch.offset))
+                .map_err(HexError::seek_failed(file_path, patch.offset))?;
+            file.write_all(&patch.bytes)
+                .map_err(HexError::write_failed(file_path, patch.offset))?;
+            writeln!(
+                writer,
+                "patched {} bytes at 0x{:08x}",
+                patch.bytes.len(),
+                patch.offset
+            )?;
+            continue;
+        }
+
+        let first_line = patch.offset / bytes_per_line * bytes_per_line;
+        let last_line = (end - 1) / bytes_per_line * bytes_per_line;
+        let mut before = vec![0; (last_line - first_line + bytes_per_line) as usize];
+        file.seek(SeekFrom::Start(first_line))
+            .map_err(HexError::seek_failed(file_path, first_line))?;
+        let n = read_full(&mut file, &mut before)
+            .map_err(HexError::read_failed(file_path, first_line))?;
+        before.truncate(n);
+
+        let start = (patch.offset - first_line) as usize;
+        let mut after = before.clone();
+        after[start..start + patch.bytes.len()].copy_from_slice(&patch.bytes);
+
+        let patched = |offset: u64| (patch.offset..end).contains(&offset);
+        for (prefix, bytes) in [("-", &before), ("+", &after)] {
+            for (i, chunk) in bytes.chunks(bytes_per_line as usize).enumerate() {
+                let line_offset = first_line + i as u64 * bytes_per_line;
+                line.clear();
+                line.push_str(prefix);
+                line.push(' ');
+                let marked = |i: usize| patched(line_offset + i as u64);
+                renderer.format_marked_line(line_offset, chunk, marked, &mut line);
+                writeln!(writer, "{}", line)?;
+            }
+        }
+    }
+    if args.dry_run {
+        writeln!(writer, "dry run, {} is not changed", file_path.display())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_patch() {
+        assert_eq!(
+            "0x10=dead beef".parse::<Patch>(),
+            Ok(Patch {
+                offset: 16,
+                bytes: vec![0xde, 0xad, 0xbe, 0xef],
+            })
+        );
+        assert_eq!("3=00".parse::<Patch>().unwrap().offset, 3);
+        assert!("0x10".parse::<Patch>().is_err());
+        assert!("x=00".parse::<Patch>().is_err());
+        assert!("0=0".parse::<Patch>().is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// time between progress line updates
+const REPORT_INTERVAL: Duration = Duration::from_millis(200);
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Progress of a dump shown on stderr, redrawn in place
+pub struct Progress {
+    total: Option<u64>,
+    done: u64,
+    started: Instant,
+    last_report: Instant,
+}
+
+impl Progress {
+    /// Progress when output is redirected and stderr is a terminal to draw on,
+    /// `None` with `--quiet-progress`
+    pub fn start(total: Option<u64>, quiet: bool) -> Option<Self> {
+        if quiet || io::stdout().is_terminal() || !io::stderr().is_terminal() {
+            return None;
+        }
+
+        let now = Instant::now();
+        Some(Progress {
+            total,
+            done: 0,
+            started: now,
+            last_report: now,
+        })
+    }
+
+    pub fn advance(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.last_report.elapsed() >= REPORT_INTERVAL {
+            self.last_report = Instant::now();
+            eprint!("\r{}", self.status());
+            // progress isn't worth failing the dump for
+            let _ = io::stderr().flush();
+        }
+    }
+
+    /// Replaces progress line with summary
+    pub fn finish(self) {
+        eprintln!("\r\x1b[K{} bytes dumped", self.done);
+    }
+
+    fn status(&self) -> String {
+        let elapsed = self.started.elapsed().as_secs_f64().max(f64::EPSILON);
+        let rate = self.done as f64 / MIB / elapsed;
+        let done = self.done as f64 / MIB;
+        match self.total {
+            Some(total) if total > 0 => format!(
+                "{:.1} / {:.1} MiB ({}%) {:.1} MiB/s",
+                done,
+                total as f64 / MIB,
+                self.done * 100 / total,
+                rate
+            ),
+            _ => format!("{:.1} MiB {:.1} MiB/s", done, rate),
+        }
+    }
+}
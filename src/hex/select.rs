@@ -0,0 +1,67 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use super::args::HexArgs;
+use super::error::{HexError, Result};
+use super::format::{HexRenderer, HexWriter};
+use crate::view::{RangeCount, RangePos, RangeSpec};
+
+/// Dumps bytes inside range with their real offsets, byte positions start from 1 like `view -c`.
+/// Every gap between selected runs of bytes is marked with a '*' line.
+pub fn dump_selected_bytes<R: BufRead, W: Write>(
+    file_path: &Path,
+    reader: &mut R,
+    total: u64,
+    spec: &RangeSpec,
+    args: &HexArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let ranges = spec.normalize(total as RangeCount);
+    let last_byte = ranges.last_line();
+    let mut hex_writer = HexWriter::new(HexRenderer::from_args(args), writer);
+    // bytes consumed before current buffer
+    let mut pos: RangePos = 0;
+    let mut in_gap = true;
+    let mut dumped_any = false;
+
+    loop {
+        let buffer = reader.fill_buf().map_err(HexError::read_failed(file_path, pos as u64))?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        let mut start = 0;
+        while start < buffer.len() {
+            let selected = ranges.contains(pos + start as RangePos + 1);
+            let end = (start + 1..buffer.len())
+                .find(|i| ranges.contains(pos + *i as RangePos + 1) != selected)
+                .unwrap_or(buffer.len());
+
+            if !selected {
+                in_gap = true;
+            } else {
+                if in_gap {
+                    hex_writer.jump_to((pos + start as RangePos) as u64)?;
+                    if dumped_any {
+                        writeln!(hex_writer.get_mut(), "*")?;
+                    }
+                    in_gap = false;
+                }
+                hex_writer.write_all(&buffer[start..end])?;
+                dumped_any = true;
+            }
+            start = end;
+        }
+
+        let len = buffer.len();
+        reader.consume(len);
+        pos += len as RangePos;
+
+        if last_byte.is_some_and(|last| pos >= last) {
+            break;
+        }
+    }
+    hex_writer.finish()?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// File operand of line based commands like `paste`, where `-` stands for standard input and
+/// may be given more than once, as in `paste - -`. Standard input is locked for each read
+/// only, so all `-` operands take turns reading the one shared stream.
+pub enum Input {
+    Stdin,
+    Reader(Box<dyn BufRead>),
+}
+
+impl Input {
+    /// Opens file, errors name its path
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if path == Path::new("-") {
+            return Ok(Input::Stdin);
+        }
+        let file = File::open(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Ok(Input::Reader(Box::new(BufReader::new(file))))
+    }
+
+    pub fn read_until(&mut self, byte: u8, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        match self {
+            Input::Stdin => io::stdin().lock().read_until(byte, buffer),
+            Input::Reader(reader) => reader.read_until(byte, buffer),
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Stdin => io::stdin().lock().read(buffer),
+            Input::Reader(reader) => reader.read(buffer),
+        }
+    }
+}
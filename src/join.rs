@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+
+use crate::input::Input;
+
+#[derive(Debug, Parser)]
+pub struct JoinArgs {
+    #[arg(
+        index = 1,
+        help = "First file sorted on its join field, standard input when '-'"
+    )]
+    pub left: PathBuf,
+
+    #[arg(
+        index = 2,
+        help = "Second file sorted on its join field, standard input when '-'"
+    )]
+    pub right: PathBuf,
+
+    #[arg(
+        short = '1',
+        value_name = "FIELD",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Join on this field of first file"
+    )]
+    pub left_field: u32,
+
+    #[arg(
+        short = '2',
+        value_name = "FIELD",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Join on this field of second file"
+    )]
+    pub right_field: u32,
+
+    #[arg(
+        short = 't',
+        long,
+        value_name = "CHAR",
+        help = "Field separator, runs of whitespace separate fields when not specified"
+    )]
+    pub separator: Option<char>,
+
+    #[arg(
+        short = 'a',
+        value_name = "FILE",
+        value_parser = clap::value_parser!(u8).range(1..=2),
+        help = "Also print lines of file 1 or 2 without a match, repeat for both"
+    )]
+    pub unpaired: Vec<u8>,
+}
+
+/// Prints lines of two files sorted on join field which have equal join fields, like `join`
+pub fn join(args: JoinArgs) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    join_to(&args, &mut writer)?;
+    writer.flush()
+}
+
+pub fn join_to<W: Write>(args: &JoinArgs, writer: &mut W) -> io::Result<()> {
+    let left = Table::open(&args.left, args.left_field, args)?;
+    let right = Table::open(&args.right, args.right_field, args)?;
+    join_tables(left, right, args, writer)
+}
+
+/// Input read in groups of consecutive lines with the same join field
+struct Table<'a> {
+    input: Input,
+    path: &'a Path,
+    field: usize,
+    separator: Option<char>,
+    /// first line of next group, already read to find end of current one
+    next: Option<Vec<String>>,
+    line_no: usize,
+}
+
+impl<'a> Table<'a> {
+    fn open(path: &'a Path, field: u32, args: &JoinArgs) -> io::Result<Self> {
+        Self::new(Input::open(path)?, path, field, args.separator)
+    }
+
+    fn new(input: Input, path: &'a Path, field: u32, separator: Option<char>) -> io::Result<Self> {
+        let mut table = Table {
+            input,
+            path,
+            field: field as usize - 1,
+            separator,
+            next: None,
+            line_no: 0,
+        };
+        table.next = table.read_fields()?;
+        Ok(table)
+    }
+
+    fn read_fields(&mut self) -> io::Result<Option<Vec<String>>> {
+        let mut line = vec![];
+        if self.input.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        self.line_no += 1;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        let fields = match self.separator {
+            Some(separator) => line.split(separator).map(str::to_string).collect(),
+            None => line.split_whitespace().map(str::to_string).collect(),
+        };
+        Ok(Some(fields))
+    }
+
+    fn key<'f>(&self, fields: &'f [String]) -> &'f str {
+        fields.get(self.field).map_or("", String::as_str)
+    }
+
+    /// Lines sharing join field of next line, empty at end of input
+    fn next_group(&mut self) -> io::Result<Vec<Vec<String>>> {
+        let Some(first) = self.next.take() else {
+            return Ok(vec![]);
+        };
+        let mut group = vec![first];
+        while let Some(fields) = self.read_fields()? {
+            match self.key(&fields).cmp(self.key(&group[0])) {
+                Ordering::Equal => group.push(fields),
+                Ordering::Greater => {
+                    self.next = Some(fields);
+                    break;
+                }
+                Ordering::Less => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "{}: line {} is not sorted on join field",
+                            self.path.display(),
+                            self.line_no
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(group)
+    }
+}
+
+fn join_tables<W: Write>(
+    mut left: Table,
+    mut right: Table,
+    args: &JoinArgs,
+    writer: &mut W,
+) -> io::Result<()> {
+    let separator = args.separator.map_or(" ".to_string(), String::from);
+    let join_fields = [left.field, right.field];
+    let write_line = |key: &str, fields: &[&[String]], writer: &mut W| -> io::Result<()> {
+        let mut line = key.to_string();
+        for (fields, join_field) in fields.iter().zip(join_fields) {
+            for (i, field) in fields.iter().enumerate() {
+                if i != join_field {
+                    line.push_str(&separator);
+                    line.push_str(field);
+                }
+            }
+        }
+        writeln!(writer, "{}", line)
+    };
+
+    let mut left_group = left.next_group()?;
+    let mut right_group = right.next_group()?;
+    while !left_group.is_empty() || !right_group.is_empty() {
+        let order = match (left_group.first(), right_group.first()) {
+            (Some(l), Some(r)) => left.key(l).cmp(right.key(r)),
+            (Some(_), None) => Ordering::Less,
+            _ => Ordering::Greater,
+        };
+        match order {
+            Ordering::Equal => {
+                let key = left.key(&left_group[0]).to_string();
+                for l in &left_group {
+                    for r in &right_group {
+                        write_line(&key, &[l, r], writer)?;
+                    }
+                }
+                left_group = left.next_group()?;
+                right_group = right.next_group()?;
+            }
+            Ordering::Less => {
+                if args.unpaired.contains(&1) {
+                    for l in &left_group {
+                        write_line(left.key(l), &[l, &[]], writer)?;
+                    }
+                }
+                left_group = left.next_group()?;
+            }
+            Ordering::Greater => {
+                if args.unpaired.contains(&2) {
+                    for r in &right_group {
+                        write_line(right.key(r), &[&[], r], writer)?;
+                    }
+                }
+                right_group = right.next_group()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join_text(args: &[&str], left: &'static str, right: &'static str) -> String {
+        let args = JoinArgs::parse_from(["join", "a", "b"].iter().chain(args));
+        let left = Table::new(
+            Input::Reader(Box::new(left.as_bytes())),
+            &args.left,
+            args.left_field,
+            args.separator,
+        )
+        .unwrap();
+        let right = Table::new(
+            Input::Reader(Box::new(right.as_bytes())),
+            &args.right,
+            args.right_field,
+            args.separator,
+        )
+        .unwrap();
+        let mut output = vec![];
+        join_tables(left, right, &args, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_join() {
+        let left = "a 1\nb 2\nb 3\nd 4\n";
+        let right = "b x\nc y\nd z\n";
+        assert_eq!(join_text(&[], left, right), "b 2 x\nb 3 x\nd 4 z\n");
+        assert_eq!(
+            join_text(&["-a", "1", "-a", "2"], left, right),
+            "a 1\nb 2 x\nb 3 x\nc y\nd 4 z\n"
+        );
+        assert_eq!(
+            join_text(&["-t", ",", "-2", "2"], "1,a\n2,b\n", "x,1\ny,2\n"),
+            "1,a,x\n2,b,y\n"
+        );
+    }
+}
@@ -1,11 +1,43 @@
+pub mod cancel;
 pub mod cli;
+pub mod cmp;
+pub mod color;
+pub mod config;
 pub mod echo;
+pub mod exit;
+pub mod find;
+pub mod fold;
 pub mod grep;
+pub mod head;
 pub mod hex;
+pub mod input;
+pub mod join;
+pub mod logging;
+pub mod mangen;
+pub mod nl;
+pub mod output;
+pub mod paste;
+pub mod paths;
+pub mod plugin;
+pub mod replace;
+pub mod report;
+pub mod tail;
+pub mod tee;
 pub mod view;
 
 pub use cli::{CliError, Parser, Result};
-pub use echo::{EchoArgs, EchoError, echo};
-pub use grep::{GrepArgs, GrepError, grep};
-pub use hex::{HexArgs, view_hex};
-pub use view::{ViewArgs, ViewError, view_files};
+pub use cmp::{CmpArgs, CmpError, cmp, cmp_to};
+pub use echo::{EchoArgs, EchoError, echo, echo_to};
+pub use find::{FindArgs, find, find_to};
+pub use fold::{FoldArgs, fold, fold_to};
+pub use grep::{GrepArgs, GrepError, grep, grep_to};
+pub use head::{HeadArgs, head};
+pub use hex::{HexArgs, view_hex, view_hex_to};
+pub use join::{JoinArgs, join, join_to};
+pub use mangen::{MangenArgs, generate_man_pages};
+pub use nl::{NlArgs, nl, nl_to};
+pub use paste::{PasteArgs, paste, paste_to};
+pub use replace::{ReplaceArgs, replace, replace_to};
+pub use tail::{TailArgs, tail};
+pub use tee::{TeeArgs, tee, tee_to};
+pub use view::{ViewArgs, ViewError, view_files, view_files_to};
@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Writes log records of `log` macros to stderr, prefixed with time since start of process
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+static START: OnceLock<Instant> = OnceLock::new();
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let elapsed = START.get_or_init(Instant::now).elapsed();
+        eprintln!(
+            "[{:>8.3}s {:<5} {}] {}",
+            elapsed.as_secs_f64(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+/// Enables logging for global `-L` given `verbosity` times, 1 logs phases and their timing,
+/// 2 every opened or skipped file and 3 everything
+pub fn init(verbosity: u8) {
+    START.get_or_init(Instant::now);
+    let level = match verbosity {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    // fails only when a logger is already set, e.g. by a program using kf as library
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,48 +1,128 @@
+use std::env;
+use std::io::{self, Write};
 use std::process;
 
-use kf::{CliError, Parser, Result, cli, echo, grep, hex, view};
+use kf::cancel;
+use kf::color;
+use kf::config::Config;
+use kf::exit::{self, ExitCode};
+use kf::logging;
+use kf::output::OutputFile;
+use kf::plugin;
+use kf::{
+    CliError, CmpError, Result, cli, cmp, echo, find, fold, grep, head, hex, join, mangen, nl,
+    paste, replace, tail, tee, view,
+};
 
 fn main() {
-    match try_main() {
-        Ok(_) => process::exit(0),
-        Err(CliError::Usage(msg)) => {
-            eprintln!("wrong usage: {}", msg);
-            process::exit(2);
-        }
-        Err(CliError::Grep(e)) => match e {
-            kf::GrepError::NoMatches => {
-                // grep convention exit 1 when no matches
-                eprintln!("grep error: {}", e);
-                process::exit(1);
+    if let Err(e) = try_main()
+        && !e.is_broken_pipe()
+    {
+        match &e {
+            // message may carry partial results of command, like grep's counts
+            e if e.is_interrupted() => {
+                if let Some(e) = e.io_error() {
+                    eprintln!("{}", e)
+                }
             }
-            _ => {
-                eprintln!("grep error: {}", e);
-                process::exit(2);
+            // keeps clap's styling, suggestions and usage, help and version go to stdout
+            CliError::Usage(e) => {
+                let _ = e.print();
+            }
+            CliError::Grep(e) => eprintln!("grep error: {}", e),
+            CliError::View(e) => eprintln!("view error: {}", e),
+            CliError::Echo(e) => eprintln!("echo error: {}", e),
+            CliError::Config(e) => eprintln!("config error: {}", e),
+            CliError::Hex(_) => eprintln!("{}", e),
+            // difference is reported by output and exit code
+            CliError::Cmp(CmpError::Differ) => {}
+            CliError::Cmp(e) => eprintln!("cmp error: {}", e),
+            CliError::Output { .. } | CliError::ArgFile { .. } | CliError::Io(_) => {
+                eprintln!("error: {}", e)
             }
-        },
-        Err(CliError::View(e)) => {
-            eprintln!("view error: {}", e);
-            process::exit(3);
-        }
-        Err(CliError::Echo(e)) => {
-            eprintln!("echo error: {}", e);
-            process::exit(3);
-        }
-        Err(CliError::Hex(e)) => {
-            eprintln!("{}", e);
-            process::exit(3);
         }
+        process::exit(e.exit_code());
     }
+    process::exit(exit::SUCCESS);
 }
 
 fn try_main() -> Result<()> {
-    let cli = cli::Cli::try_parse().map_err(|e| CliError::Usage(e.to_string()))?;
+    color::enable_terminal_colors();
+    let config = Config::load()?;
+    let args = cli::expand_arg_files(env::args_os().collect())?;
+    if let Some((path, plugin_args)) = cli::find_plugin(&args, &config) {
+        let code = plugin::run_plugin(&path, &plugin_args)?;
+        process::exit(code);
+    }
+
+    let mut cli = cli::Cli::parse_with_config(args, &config)?;
+    logging::init(cli.log);
+    cli.command.set_color(cli.color);
+    cli.command.set_dry_run(cli.dry_run);
+    if cli.command.polls_cancel() {
+        cancel::install_handler().map_err(|e| CliError::Io(io::Error::other(e)))?;
+    }
+
+    let Some(path) = &cli.output else {
+        return run(cli.command);
+    };
+    if cli.dry_run {
+        run(cli.command)?;
+        eprintln!("dry run, {} is not written", path.display());
+        return Ok(());
+    }
+
+    // output file is never a terminal
+    colored::control::set_override(cli.color.enabled_for(false));
+    let output_error = |source| CliError::Output {
+        path: path.clone(),
+        source,
+    };
+    let mut output = OutputFile::create(path, cli.append, cli.atomic).map_err(output_error)?;
+    run_to(cli.command, &mut output)?;
+    output.commit().map_err(output_error)
+}
 
-    match cli.command {
+fn run(command: cli::Command) -> Result<()> {
+    match command {
         cli::Command::Grep(args) => grep::grep(args)?,
         cli::Command::View(args) => view::view_files(args)?,
         cli::Command::Echo(args) => echo::echo(args)?,
         cli::Command::Hex(args) => hex::view_hex(args)?,
+        cli::Command::Head(args) => head::head(args)?,
+        cli::Command::Tail(args) => tail::tail(args)?,
+        cli::Command::Nl(args) => nl::nl(args)?,
+        cli::Command::Find(args) => find::find(args)?,
+        cli::Command::Tee(args) => tee::tee(args)?,
+        cli::Command::Fold(args) => fold::fold(args)?,
+        cli::Command::Cmp(args) => cmp::cmp(args)?,
+        cli::Command::Replace(args) => replace::replace(args)?,
+        cli::Command::Paste(args) => paste::paste(args)?,
+        cli::Command::Join(args) => join::join(args)?,
+        cli::Command::Mangen(args) => mangen::mangen(args)?,
+    }
+
+    Ok(())
+}
+
+/// Runs command with its output written to given writer instead of stdout
+fn run_to<W: Write>(command: cli::Command, writer: &mut W) -> Result<()> {
+    match command {
+        cli::Command::Grep(args) => grep::grep_to(&args, writer)?,
+        cli::Command::View(args) => view::view_files_to(&args, writer)?,
+        cli::Command::Echo(args) => echo::echo_to(&args, writer)?,
+        cli::Command::Hex(args) => hex::view_hex_to(&args, writer)?,
+        cli::Command::Head(args) => view::view_files_to(&args.into_view_args(), writer)?,
+        cli::Command::Tail(args) => view::view_files_to(&args.into_view_args(), writer)?,
+        cli::Command::Nl(args) => nl::nl_to(&args, writer)?,
+        cli::Command::Find(args) => find::find_to(&args, writer)?,
+        cli::Command::Tee(args) => tee::tee_to(&args, &mut io::stdin().lock(), writer)?,
+        cli::Command::Fold(args) => fold::fold_to(&args, writer)?,
+        cli::Command::Cmp(args) => cmp::cmp_to(&args, writer)?,
+        cli::Command::Replace(args) => replace::replace_to(&args, writer)?,
+        cli::Command::Paste(args) => paste::paste_to(&args, writer)?,
+        cli::Command::Join(args) => join::join_to(&args, writer)?,
+        cli::Command::Mangen(args) => mangen::mangen_to(&args, writer)?,
     }
 
     Ok(())
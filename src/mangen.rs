@@ -0,0 +1,70 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{CommandFactory, Parser};
+
+use crate::cli::Cli;
+
+#[derive(Debug, Parser)]
+pub struct MangenArgs {
+    #[arg(
+        index = 1,
+        value_name = "DIR",
+        help = "Directory to write 'kf.1' and a page per subcommand to, 'kf.1' is printed when omitted"
+    )]
+    pub out_dir: Option<PathBuf>,
+
+    /// set from global `--dry-run`, lists pages instead of writing them
+    #[arg(skip)]
+    pub dry_run: bool,
+}
+
+/// Writes man pages of kf and its subcommands, or prints the main page
+pub fn mangen(args: MangenArgs) -> io::Result<()> {
+    mangen_to(&args, &mut io::stdout().lock())
+}
+
+/// Like `mangen`, the main page or pages listed by `--dry-run` go to writer
+pub fn mangen_to<W: Write>(args: &MangenArgs, writer: &mut W) -> io::Result<()> {
+    match &args.out_dir {
+        Some(out_dir) if args.dry_run => {
+            for path in man_page_paths(Cli::command(), out_dir) {
+                writeln!(writer, "would write {}", path.display())?;
+            }
+            Ok(())
+        }
+        Some(out_dir) => {
+            fs::create_dir_all(out_dir)?;
+            generate_man_pages(out_dir)
+        }
+        None => write_man_page(writer),
+    }
+}
+
+/// Generates roff man pages named like `kf.1` and `kf-grep.1` into given directory
+pub fn generate_man_pages(out_dir: &Path) -> io::Result<()> {
+    clap_mangen::generate_to(Cli::command(), out_dir)
+}
+
+/// Files written by `generate_man_pages`, subcommand pages come first like it writes them
+fn man_page_paths(command: clap::Command, out_dir: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![];
+    let mut command = command.disable_help_subcommand(true);
+    command.build();
+    collect_man_page_paths(&command, out_dir, &mut paths);
+    paths
+}
+
+fn collect_man_page_paths(command: &clap::Command, out_dir: &Path, paths: &mut Vec<PathBuf>) {
+    for subcommand in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        collect_man_page_paths(subcommand, out_dir, paths);
+    }
+    let file_name = clap_mangen::Man::new(command.clone()).get_filename();
+    paths.push(out_dir.join(file_name));
+}
+
+/// Renders man page of top level command
+pub fn write_man_page<W: Write>(writer: &mut W) -> io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(writer)
+}
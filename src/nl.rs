@@ -0,0 +1,217 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, ValueEnum};
+
+use crate::report;
+use crate::view::{Gutter, RangePos};
+
+#[derive(Debug, Parser)]
+pub struct NlArgs {
+    #[arg(
+        index = 1,
+        help = "Files to number lines of, standard input when not specified or file is '-'"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'b',
+        long,
+        value_name = "STYLE",
+        value_enum,
+        default_value_t = Numbering::NonEmpty,
+        help = "Lines to number"
+    )]
+    pub body_numbering: Numbering,
+
+    #[arg(
+        short = 'v',
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        allow_negative_numbers = true,
+        help = "Number of first line"
+    )]
+    pub starting_line_number: RangePos,
+
+    #[arg(
+        short = 'i',
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        allow_negative_numbers = true,
+        help = "Step between numbers of numbered lines"
+    )]
+    pub line_increment: RangePos,
+
+    #[arg(
+        short = 'w',
+        long,
+        value_name = "N",
+        default_value_t = 6,
+        help = "Minimum width of line numbers"
+    )]
+    pub number_width: usize,
+
+    #[arg(
+        short = 's',
+        long,
+        value_name = "STRING",
+        default_value = "\\t",
+        value_parser = |value: &str| Ok::<_, String>(value.replace("\\t", "\t")),
+        help = "Separator after line numbers, '\\t' stands for a tab"
+    )]
+    pub number_separator: String,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = NumberAlign::Rn,
+        help = "Alignment of line numbers"
+    )]
+    pub number_format: NumberAlign,
+}
+
+/// Lines that get a number, unnumbered lines don't advance it
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Numbering {
+    /// Every line
+    #[value(alias = "a")]
+    All,
+    /// Lines with content, like `nl -b t`
+    #[value(alias = "t")]
+    NonEmpty,
+    /// No line
+    #[value(alias = "n")]
+    None,
+}
+
+/// Same names as `nl -n`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum NumberAlign {
+    /// Left aligned
+    Ln,
+    /// Right aligned
+    Rn,
+    /// Right aligned, padded with zeros
+    Rz,
+}
+
+/// Prints lines of all files numbered continuously, like `nl`
+pub fn nl(args: NlArgs) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    nl_to(&args, &mut writer)?;
+    writer.flush()
+}
+
+pub fn nl_to<W: Write>(args: &NlArgs, writer: &mut W) -> io::Result<()> {
+    let mut numberer = LineNumberer::new(args);
+
+    if args.file_paths.is_empty() {
+        return numberer.number_lines(&mut io::stdin().lock(), writer);
+    }
+    // like `kf view`, an unreadable file is reported and the rest are still numbered
+    let mut failed = 0;
+    for file_path in &args.file_paths {
+        let result = if file_path == Path::new("-") {
+            numberer.number_lines(&mut io::stdin().lock(), writer)
+        } else {
+            File::open(file_path)
+                .and_then(|file| numberer.number_lines(&mut BufReader::new(file), writer))
+        };
+        match result {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Err(e),
+            Err(e) => {
+                report::file_error("nl", format_args!("{}: {}", file_path.display(), e));
+                failed += 1;
+            }
+            Ok(()) => {}
+        }
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "unreadable files skipped: {}",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+struct LineNumberer {
+    gutter: Gutter,
+    numbering: Numbering,
+    next: RangePos,
+    increment: RangePos,
+}
+
+impl LineNumberer {
+    fn new(args: &NlArgs) -> Self {
+        let gutter = Gutter::new(
+            args.number_width,
+            args.number_separator.clone(),
+            args.number_format == NumberAlign::Rz,
+            args.number_format == NumberAlign::Ln,
+        );
+        LineNumberer {
+            gutter,
+            numbering: args.body_numbering,
+            next: args.starting_line_number,
+            increment: args.line_increment,
+        }
+    }
+
+    /// Lines are copied as bytes, so any encoding passes through unchanged
+    fn number_lines<R: BufRead, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let mut line = vec![];
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            let blank = matches!(line.as_slice(), b"\n" | b"\r\n");
+            let numbered = match self.numbering {
+                Numbering::All => true,
+                Numbering::NonEmpty => !blank,
+                Numbering::None => false,
+            };
+
+            if numbered {
+                write!(writer, "{}", self.gutter.number(self.next))?;
+                self.next += self.increment;
+            } else {
+                // like `nl`, also blank lines
+                write!(writer, "{}", self.gutter.blank())?;
+            }
+            writer.write_all(&line)?;
+            line.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nl() {
+        let args = NlArgs::parse_from(["nl", "-v", "10", "-i", "5", "-w", "3", "-s", ": "]);
+        assert_eq!(number_lines(&args, b"a\n\nb"), " 10: a\n     \n 15: b");
+
+        let args = NlArgs::parse_from(["nl", "-b", "a", "-n", "rz", "-w", "2"]);
+        assert_eq!(number_lines(&args, b"a\n\n"), "01\ta\n02\t\n");
+
+        let args = NlArgs::parse_from(["nl", "-b", "n", "-w", "2", "-s", " "]);
+        assert_eq!(number_lines(&args, b"a\n"), "   a\n");
+    }
+
+    fn number_lines(args: &NlArgs, mut input: &[u8]) -> String {
+        let mut output = vec![];
+        LineNumberer::new(args).number_lines(&mut input, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+}
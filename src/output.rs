@@ -0,0 +1,68 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// File behind global `--output`. With `--atomic` content goes to a temporary file next to it,
+/// which replaces the file only when the command succeeded.
+pub struct OutputFile {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    /// renamed to `path` by `commit`, removed when dropped before
+    temp_path: Option<PathBuf>,
+}
+
+impl OutputFile {
+    pub fn create(path: &Path, append: bool, atomic: bool) -> io::Result<Self> {
+        let temp_path = atomic.then(|| temp_path_for(path));
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(temp_path.as_deref().unwrap_or(path))?;
+
+        Ok(OutputFile {
+            writer: BufWriter::new(file),
+            path: path.to_path_buf(),
+            temp_path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Flushes written content and moves temporary file of atomic mode in place
+    pub fn commit(mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        if let Some(temp_path) = self.temp_path.take() {
+            fs::rename(&temp_path, &self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        if let Some(temp_path) = &self.temp_path {
+            let _ = fs::remove_file(temp_path);
+        }
+    }
+}
+
+/// Hidden file in the same directory, so that rename doesn't cross file systems
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.kf-{}.tmp", name, process::id()))
+}
@@ -0,0 +1,195 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::input::Input;
+
+#[derive(Debug, Parser)]
+pub struct PasteArgs {
+    #[arg(
+        index = 1,
+        help = "Files whose lines are merged, standard input when not specified or file is '-'"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'd',
+        long,
+        value_name = "LIST",
+        default_value = "\\t",
+        value_parser = parse_delimiters,
+        help = "Delimiters used in turn between columns, '\\t', '\\n', '\\\\' and '\\0' for none are recognized"
+    )]
+    pub delimiters: Delimiters,
+
+    #[arg(
+        short = 's',
+        long,
+        help = "Merge all lines of each file into one line instead of lines of all files"
+    )]
+    pub serial: bool,
+}
+
+/// Delimiters of `--delimiters`, an empty one joins columns directly
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delimiters(Vec<String>);
+
+impl Delimiters {
+    /// Delimiter after column `i`, the list is reused from start when used up
+    fn after(&self, i: usize) -> &str {
+        &self.0[i % self.0.len()]
+    }
+}
+
+/// Merges corresponding lines of files into columns, like `paste`
+pub fn paste(args: PasteArgs) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(io::stdout().lock());
+    paste_to(&args, &mut writer)?;
+    writer.flush()
+}
+
+pub fn paste_to<W: Write>(args: &PasteArgs, writer: &mut W) -> io::Result<()> {
+    let stdin_only = [PathBuf::from("-")];
+    let file_paths = if args.file_paths.is_empty() {
+        &stdin_only[..]
+    } else {
+        &args.file_paths[..]
+    };
+
+    let mut inputs = vec![];
+    for file_path in file_paths {
+        inputs.push(Input::open(file_path)?);
+    }
+    if args.serial {
+        for input in &mut inputs {
+            paste_serial(input, &args.delimiters, writer)?;
+        }
+        Ok(())
+    } else {
+        paste_parallel(&mut inputs, &args.delimiters, writer)
+    }
+}
+
+/// Writes one line per line number, files that ended give empty columns
+fn paste_parallel<W: Write>(
+    inputs: &mut [Input],
+    delimiters: &Delimiters,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut columns = vec![vec![]; inputs.len()];
+    loop {
+        let mut any_line = false;
+        for (input, column) in inputs.iter_mut().zip(&mut columns) {
+            column.clear();
+            any_line |= read_line(input, column)?;
+        }
+        if !any_line {
+            return Ok(());
+        }
+        write_columns(&columns, delimiters, writer)?;
+    }
+}
+
+/// Writes all lines of input as columns of one line
+fn paste_serial<W: Write>(
+    input: &mut Input,
+    delimiters: &Delimiters,
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut columns = vec![];
+    let mut column = vec![];
+    while read_line(input, &mut column)? {
+        columns.push(std::mem::take(&mut column));
+    }
+    write_columns(&columns, delimiters, writer)
+}
+
+/// Reads line without its terminator as bytes, any encoding is passed through
+fn read_line(input: &mut Input, line: &mut Vec<u8>) -> io::Result<bool> {
+    if input.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    if line.ends_with(b"\n") {
+        line.pop();
+        if line.ends_with(b"\r") {
+            line.pop();
+        }
+    }
+    Ok(true)
+}
+
+fn write_columns<W: Write>(
+    columns: &[Vec<u8>],
+    delimiters: &Delimiters,
+    writer: &mut W,
+) -> io::Result<()> {
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(delimiters.after(i - 1).as_bytes())?;
+        }
+        writer.write_all(column)?;
+    }
+    writeln!(writer)
+}
+
+fn parse_delimiters(value: &str) -> Result<Delimiters, String> {
+    let mut delimiters = vec![];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let delimiter = match c {
+            '\\' => match chars.next() {
+                Some('t') => "\t".to_string(),
+                Some('n') => "\n".to_string(),
+                Some('\\') => "\\".to_string(),
+                Some('0') => String::new(),
+                Some(c) => return Err(format!("unknown escape '\\{}'", c)),
+                None => return Err("trailing '\\'".to_string()),
+            },
+            c => c.to_string(),
+        };
+        delimiters.push(delimiter);
+    }
+
+    if delimiters.is_empty() {
+        return Err("no delimiters, use '\\0' to join columns directly".to_string());
+    }
+    Ok(Delimiters(delimiters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste() {
+        let delimiters = parse_delimiters(",;").unwrap();
+        let mut inputs = [
+            Input::Reader(Box::new(&b"a\nb\nc\n"[..])),
+            Input::Reader(Box::new(&b"1\n2\n"[..])),
+            Input::Reader(Box::new(&b"x\xff\n"[..])),
+        ];
+        let mut output = vec![];
+        paste_parallel(&mut inputs, &delimiters, &mut output).unwrap();
+        assert_eq!(output, b"a,1;x\xff\nb,2;\nc,;\n");
+
+        let mut input = Input::Reader(Box::new(&b"a\nb\nc\n"[..]));
+        let mut output = vec![];
+        paste_serial(&mut input, &parse_delimiters("\\t").unwrap(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a\tb\tc\n");
+    }
+
+    #[test]
+    fn test_parse_delimiters() {
+        assert_eq!(
+            parse_delimiters("\\t\\0,"),
+            Ok(Delimiters(vec![
+                "\t".to_string(),
+                String::new(),
+                ",".to_string()
+            ]))
+        );
+        assert!(parse_delimiters("").is_err());
+        assert!(parse_delimiters("\\x").is_err());
+    }
+}
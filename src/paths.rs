@@ -0,0 +1,28 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Path as printed in output. Windows accepts `/` too, so paths joined from arguments like
+/// `src/` and walked entries would mix separators, they're all printed as `\` there.
+pub fn native(path: &Path) -> Cow<'_, str> {
+    let text = path.to_string_lossy();
+    if cfg!(windows) && text.contains('/') {
+        Cow::Owned(text.replace('/', "\\"))
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native() {
+        let expected = if cfg!(windows) {
+            "src\\grep\\mod.rs"
+        } else {
+            "src/grep/mod.rs"
+        };
+        assert_eq!(native(&Path::new("src/grep").join("mod.rs")), expected);
+    }
+}
@@ -0,0 +1,127 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli;
+use crate::config::PluginConfig;
+use crate::exit;
+
+/// `kf foo ARGS...` runs executable `kf-foo` found on PATH with `ARGS...`, like git does
+const PLUGIN_PREFIX: &str = "kf-";
+
+/// global flag disabling plugins, declared in `Cli` so it shows in help
+pub const NO_PLUGINS_FLAG: &str = "--no-plugins";
+
+/// Name of plugin that command line asks for. `reserved` tells whether a name means something
+/// else to kf, like its own commands, which always take precedence.
+pub fn requested_plugin<'a>(
+    args: &'a [OsString],
+    config: &PluginConfig,
+    reserved: impl Fn(&str) -> bool,
+) -> Option<&'a str> {
+    let name = args.get(cli::subcommand_index(args)?)?.to_str()?;
+    let valid_name = !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid_name || reserved(name) || args.iter().any(|arg| arg == NO_PLUGINS_FLAG) {
+        return None;
+    }
+    match &config.allow {
+        Some(allowed) if !allowed.iter().any(|allowed| allowed == name) => None,
+        _ => Some(name),
+    }
+}
+
+/// Executable of plugin in directories of PATH
+pub fn find_plugin(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, env::consts::EXE_SUFFIX);
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// Runs plugin with given arguments and returns its exit code
+pub fn run_plugin(plugin: &Path, args: &[OsString]) -> io::Result<i32> {
+    let status = Command::new(plugin).args(args).status()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+
+        // shells report a process killed by a signal like this
+        if let Some(signal) = status.signal() {
+            return Ok(128 + signal);
+        }
+    }
+    Ok(status.code().unwrap_or(exit::IO))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsStr::new).map(OsStr::to_os_string).collect()
+    }
+
+    #[test]
+    fn test_requested_plugin() {
+        let any = PluginConfig::default();
+        let builtin = |name: &str| name == "grep";
+
+        assert_eq!(
+            requested_plugin(&args(&["kf", "foo", "-x"]), &any, builtin),
+            Some("foo")
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "grep", "x"]), &any, builtin),
+            None
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "--color"]), &any, builtin),
+            None
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "--color", "never", "foo"]), &any, builtin),
+            Some("foo")
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "../x"]), &any, builtin),
+            None
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "foo", "--no-plugins"]), &any, builtin),
+            None
+        );
+
+        let only_bar = PluginConfig {
+            allow: Some(vec!["bar".to_string()]),
+        };
+        assert_eq!(
+            requested_plugin(&args(&["kf", "foo"]), &only_bar, builtin),
+            None
+        );
+        assert_eq!(
+            requested_plugin(&args(&["kf", "bar"]), &only_bar, builtin),
+            Some("bar")
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use colored::Colorize;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+
+use crate::cancel;
+use crate::color::ColorWhen;
+use crate::grep::FilesFinder;
+use crate::output::OutputFile;
+use crate::paths;
+use crate::report;
+
+#[derive(Debug, Parser)]
+pub struct ReplaceArgs {
+    #[arg(index = 1, help = "Regex pattern, '(?i)' prefix ignores case")]
+    pub pattern: Regex,
+
+    #[arg(
+        index = 2,
+        help = "Replacement, '$1' or '${name}' insert capture groups and '$$' a dollar sign"
+    )]
+    pub replacement: String,
+
+    #[arg(
+        index = 3,
+        help = "Files edited in place, standard input is replaced to standard output when not specified"
+    )]
+    pub paths: Vec<PathBuf>,
+
+    #[arg(short, long, help = "Edit files in directories recursively")]
+    pub recursive: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = parse_glob,
+        help = "Only edit files with matching name, e.g. '*.rs'"
+    )]
+    pub include: Vec<Glob>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = parse_glob,
+        help = "Skip files and directories with matching name in recursive search, e.g. 'target'"
+    )]
+    pub ignore: Vec<Glob>,
+
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        help = "Keep original of each edited file with suffix added to its name, e.g. '--backup=.orig'"
+    )]
+    pub backup: Option<String>,
+
+    /// set from global `--color`
+    #[arg(skip)]
+    pub color: ColorWhen,
+
+    /// set from global `--dry-run`, prints changed lines instead of editing files
+    #[arg(skip)]
+    pub dry_run: bool,
+}
+
+/// Replaces pattern line by line, in files or from standard input to standard output
+pub fn replace(args: ReplaceArgs) -> io::Result<()> {
+    // colored checks terminal on its own, global --color choice has to override it
+    colored::control::set_override(args.color.enabled());
+    replace_to(&args, &mut io::stdout().lock())
+}
+
+pub fn replace_to<W: Write>(args: &ReplaceArgs, writer: &mut W) -> io::Result<()> {
+    if args.paths.is_empty() {
+        let mut line = String::new();
+        let mut reader = io::stdin().lock();
+        while reader.read_line(&mut line)? > 0 {
+            writer.write_all(replace_line(args, &line).as_bytes())?;
+            line.clear();
+        }
+        return writer.flush();
+    }
+
+    let ignore = build_glob_set(&args.ignore)?;
+    let include = build_glob_set(&args.include)?;
+    let mut changed_files = 0;
+    let mut failed = 0;
+    for file_path in FilesFinder::new(&args.paths, args.recursive, &ignore).find_files() {
+        cancel::check()?;
+        let result = file_path.and_then(|file_path| {
+            let included = args.include.is_empty()
+                || file_path.file_name().is_some_and(|name| include.is_match(name));
+            if !included {
+                return Ok(false);
+            }
+            replace_file(args, &file_path, writer)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", file_path.display(), e)))
+        });
+        match result {
+            Ok(changed) => changed_files += usize::from(changed),
+            Err(e) => {
+                report::file_error("replace", e);
+                failed += 1;
+            }
+        }
+    }
+
+    if args.dry_run {
+        writeln!(writer, "dry run, {} files would change", changed_files)?;
+    }
+    writer.flush()?;
+    if failed > 0 {
+        return Err(io::Error::other(format!("files not edited: {}", failed)));
+    }
+    Ok(())
+}
+
+/// Edits file in place, or prints its changed lines with `--dry-run`. Returns whether it changed.
+fn replace_file<W: Write>(
+    args: &ReplaceArgs,
+    file_path: &Path,
+    writer: &mut W,
+) -> io::Result<bool> {
+    let content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            log::debug!("skipped {}, not UTF-8 text", file_path.display());
+            return Ok(false);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut replaced = String::with_capacity(content.len());
+    let mut changed_lines = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let new_line = replace_line(args, line);
+        if let Cow::Owned(new_line) = &new_line
+            && new_line != line
+        {
+            changed_lines += 1;
+            if args.dry_run {
+                write_change(file_path, i + 1, line, new_line, writer)?;
+            }
+        }
+        replaced.push_str(&new_line);
+    }
+    if changed_lines == 0 || args.dry_run {
+        return Ok(changed_lines > 0);
+    }
+
+    if let Some(suffix) = &args.backup {
+        let mut backup_path = file_path.as_os_str().to_os_string();
+        backup_path.push(suffix);
+        fs::copy(file_path, backup_path)?;
+    }
+    // written beside and renamed over file, which is never left half written. A symlink is
+    // resolved first, so that its target is edited instead of link being replaced by a file.
+    let target = fs::canonicalize(file_path)?;
+    let permissions = fs::metadata(&target)?.permissions();
+    let mut output = OutputFile::create(&target, false, true)?;
+    output.write_all(replaced.as_bytes())?;
+    output.commit()?;
+    fs::set_permissions(&target, permissions)?;
+
+    writeln!(
+        writer,
+        "{}: {} lines changed",
+        paths::native(file_path),
+        changed_lines
+    )?;
+    Ok(true)
+}
+
+fn replace_line<'a>(args: &ReplaceArgs, line: &'a str) -> Cow<'a, str> {
+    args.pattern.replace_all(line, args.replacement.as_str())
+}
+
+/// Line before and after replacement like a unified diff
+fn write_change<W: Write>(
+    file_path: &Path,
+    line_no: usize,
+    old: &str,
+    new: &str,
+    writer: &mut W,
+) -> io::Result<()> {
+    writeln!(writer, "{}:{}", paths::native(file_path).magenta(), line_no)?;
+    writeln!(
+        writer,
+        "{}",
+        format!("-{}", old.trim_end_matches(['\r', '\n'])).red()
+    )?;
+    writeln!(
+        writer,
+        "{}",
+        format!("+{}", new.trim_end_matches(['\r', '\n'])).green()
+    )
+}
+
+fn build_glob_set(globs: &[Glob]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(glob.clone());
+    }
+    builder.build().map_err(io::Error::other)
+}
+
+fn parse_glob(value: &str) -> Result<Glob, String> {
+    Glob::new(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_file() {
+        let file_path = std::env::temp_dir().join("kf_replace_file.txt");
+        fs::write(&file_path, "let a = 1;\nkeep\nlet b = 2;\n").unwrap();
+        let path = file_path.to_str().unwrap();
+
+        let mut args = ReplaceArgs::parse_from(["replace", r"let (\w+)", "const ${1}", path]);
+        args.dry_run = true;
+        let mut output = vec![];
+        assert!(replace_file(&args, &file_path, &mut output).unwrap());
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "let a = 1;\nkeep\nlet b = 2;\n"
+        );
+
+        args.dry_run = false;
+        assert!(replace_file(&args, &file_path, &mut vec![]).unwrap());
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "const a = 1;\nkeep\nconst b = 2;\n"
+        );
+        fs::remove_file(file_path).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+use std::fmt::Display;
+
+/// Reports failure of one file that the command skips to go on with the others. Printed like
+/// errors that stop a command, e.g. `find error: src/x: Permission denied`.
+pub fn file_error(command: &str, error: impl Display) {
+    eprintln!("{} error: {}", command, error);
+}
@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::view::{self, Paging, RangePos, RangeSpec, ViewArgs};
+
+/// lines printed when neither `-n` nor `-c` is given, same as coreutils
+const DEFAULT_LINES: &str = "10";
+
+#[derive(Debug, Parser)]
+pub struct TailArgs {
+    #[arg(
+        index = 1,
+        help = "Files to print end of, standard input when not specified or file is '-'"
+    )]
+    pub file_paths: Vec<PathBuf>,
+
+    #[arg(
+        short = 'n',
+        long,
+        value_name = "[+]N",
+        default_value = DEFAULT_LINES,
+        allow_hyphen_values = true,
+        value_parser = parse_tail_count,
+        help = "Print last N lines, or lines from N on with '+N'"
+    )]
+    pub lines: RangeSpec,
+
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "[+]N",
+        allow_hyphen_values = true,
+        value_parser = parse_tail_count,
+        help = "Print last N bytes instead of lines, or bytes from N on with '+N'"
+    )]
+    pub bytes: Option<RangeSpec>,
+
+    #[arg(
+        short = 'f',
+        long,
+        conflicts_with = "bytes",
+        help = "Output appended lines as the file grows, for a single file, not standard input"
+    )]
+    pub follow: bool,
+
+    #[arg(
+        short = 'F',
+        conflicts_with = "bytes",
+        help = "Follow file by name and keep following when it's rotated or recreated, implies -f"
+    )]
+    pub follow_retry: bool,
+
+    #[arg(short, long, help = "Never print headers of file names")]
+    pub quiet: bool,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with = "quiet",
+        help = "Always print headers of file names"
+    )]
+    pub verbose: bool,
+}
+
+impl TailArgs {
+    /// Same selection made with `kf view`, negative ranges keep only a ring buffer of last lines
+    /// in memory while reading
+    pub fn into_view_args(self) -> ViewArgs {
+        let (lines, bytes) = match self.bytes {
+            Some(bytes) => (vec![], Some(bytes)),
+            None => (vec![self.lines], None),
+        };
+        ViewArgs {
+            file_paths: self.file_paths,
+            lines,
+            bytes,
+            follow: self.follow || self.follow_retry,
+            retry: self.follow_retry,
+            quite: self.quiet,
+            always_header: self.verbose,
+            paging: Paging::Never,
+            // bytes are passed through as they are, like coreutils
+            force_text: true,
+            ..ViewArgs::default()
+        }
+    }
+}
+
+pub fn tail(args: TailArgs) -> view::Result<()> {
+    view::view_files(args.into_view_args())
+}
+
+/// `N` or `-N` selects `-N..`, `+N` selects `N..`
+fn parse_tail_count(value: &str) -> Result<RangeSpec, String> {
+    let (from_start, digits) = match value.strip_prefix('+') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('-').unwrap_or(value)),
+    };
+    let count: RangePos = digits
+        .parse()
+        .ok()
+        .filter(|&count| count >= 0)
+        .ok_or_else(|| format!("invalid count '{}'", value))?;
+
+    Ok(match (from_start, count) {
+        (true, count) => RangeSpec::From(count.max(1)),
+        // '-0..' would be taken as '0..', which is every line
+        (false, 0) => RangeSpec::To(0),
+        (false, count) => RangeSpec::From(-count),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tail_count() {
+        assert_eq!(parse_tail_count("5"), Ok(RangeSpec::From(-5)));
+        assert_eq!(parse_tail_count("-5"), Ok(RangeSpec::From(-5)));
+        assert_eq!(parse_tail_count("+3"), Ok(RangeSpec::From(3)));
+        assert_eq!(parse_tail_count("0"), Ok(RangeSpec::To(0)));
+        assert!(parse_tail_count("+x").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::output::OutputFile;
+use crate::report;
+
+/// bytes read from input at once, each read is written through right away
+const BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Parser)]
+pub struct TeeArgs {
+    #[arg(index = 1, help = "Files to copy standard input to")]
+    pub files: Vec<PathBuf>,
+
+    // no long '--append', it's the global option of '--output'
+    #[arg(short = 'a', help = "Append to files instead of overwriting them")]
+    pub append_files: bool,
+
+    /// set from global `--dry-run`, input is only copied to standard output
+    #[arg(skip)]
+    pub dry_run: bool,
+}
+
+/// Copies standard input to standard output and files, like `tee`
+pub fn tee(args: TeeArgs) -> io::Result<()> {
+    tee_to(&args, &mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+/// Copies reader to writer and files. Files keep being written when writer is a closed pipe,
+/// a file that fails is reported and dropped while the others go on.
+pub fn tee_to<R: Read, W: Write>(args: &TeeArgs, reader: &mut R, writer: &mut W) -> io::Result<()> {
+    let mut failed = 0;
+    let mut report = |path: &PathBuf, e: io::Error| {
+        report::file_error("tee", format_args!("{}: {}", path.display(), e));
+        failed += 1;
+    };
+
+    let mut outputs = vec![];
+    let files: &[PathBuf] = if args.dry_run { &[] } else { &args.files };
+    for path in files {
+        match OutputFile::create(path, args.append_files, false) {
+            Ok(output) => outputs.push(output),
+            Err(e) => report(path, e),
+        }
+    }
+
+    let mut writer_open = true;
+    let mut buffer = vec![0; BUFFER_SIZE];
+    while writer_open || !outputs.is_empty() {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let chunk = &buffer[..n];
+
+        if writer_open {
+            match writer.write_all(chunk).and_then(|_| writer.flush()) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                    log::info!("output closed, writing files only");
+                    writer_open = false;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        outputs.retain_mut(
+            |output| match output.write_all(chunk).and_then(|_| output.flush()) {
+                Ok(()) => true,
+                Err(e) => {
+                    report(&output.path().to_path_buf(), e);
+                    false
+                }
+            },
+        );
+    }
+
+    for output in outputs {
+        let path = output.path().to_path_buf();
+        if let Err(e) = output.commit() {
+            report(&path, e);
+        }
+    }
+    if args.dry_run {
+        for path in &args.files {
+            eprintln!("dry run, {} is not written", path.display());
+        }
+    }
+    if failed > 0 {
+        return Err(io::Error::other(format!("files not written: {}", failed)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// output of a reader like `head` that quit
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tee_to_closed_pipe() {
+        let file_path = std::env::temp_dir().join("kf_tee_to_closed_pipe.txt");
+        let args = TeeArgs::parse_from(["tee", file_path.to_str().unwrap()]);
+
+        tee_to(&args, &mut &b"all of input"[..], &mut ClosedPipe).unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "all of input");
+        fs::remove_file(file_path).unwrap();
+    }
+}
@@ -1,16 +1,22 @@
+use std::borrow::Cow;
 use std::path::PathBuf;
 
-use clap::Parser;
 use clap::builder::{TypedValueParser, ValueParserFactory};
 use clap::error::ErrorKind;
+use clap::{ArgAction, Parser, ValueEnum};
 
-use super::range::RangeSpec;
+use super::format::LineEnding;
+use super::gutter::NumberFormat;
+use super::pager::Paging;
+use super::range::{ParseError, RangeSpec};
+use crate::color::ColorWhen;
+use crate::hex::{self, Columns};
 
 #[derive(Debug, Parser)]
 pub struct ViewArgs {
     #[arg(
         index = 1,
-        help = "Files to view in specified format, standard input use when not files specified"
+        help = "Files to view in specified format, standard input use when not files specified or file is '-'"
     )]
     pub file_paths: Vec<PathBuf>,
 
@@ -21,17 +27,243 @@ pub struct ViewArgs {
     )]
     pub quite: bool,
 
+    #[arg(
+        short = 'H',
+        long,
+        conflicts_with = "quite",
+        help = "Print header even for a single file or standard input"
+    )]
+    pub always_header: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "quite",
+        help = "Add size, modification time, encoding and line count to headers, implies -H"
+    )]
+    pub verbose: bool,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        default_value = "==> {path} <==",
+        help = "Header template, '{path}' is replaced with file path"
+    )]
+    pub header_format: String,
+
     #[arg(
         short = 'n',
         long,
-        help = "Lines to output. Use '-' for all lines, e.g., '1-5' or '10'",
+        help = "Lines to output. Use '-' for all lines, e.g., '1-5' or '10', repeat to select more",
         default_value = "-",
+        action = ArgAction::Append,
         // required to work with default_missing_value
         num_args = 0..=1,
         default_missing_value = "-",
+        // negative positions like '-10..' are values, not flags
+        allow_hyphen_values = true,
+        value_parser = clap::value_parser!(RangeSpec))
+    ]
+    pub lines: Vec<RangeSpec>,
+
+    #[arg(
+        short = 'c',
+        long,
+        help = "Bytes to output instead of lines, e.g., '1025..2048' or '-16..'",
+        allow_hyphen_values = true,
+        conflicts_with = "lines",
+        value_parser = clap::value_parser!(RangeSpec))
+    ]
+    pub bytes: Option<RangeSpec>,
+
+    #[arg(
+        long,
+        help = "Characters of each line to output, e.g., '1..80' or '-10..'",
+        allow_hyphen_values = true,
+        conflicts_with = "bytes",
         value_parser = clap::value_parser!(RangeSpec))
     ]
-    pub lines: RangeSpec,
+    pub chars: Option<RangeSpec>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["lines", "bytes", "in_range_order"],
+        help = "Skip first N lines, cheaper than '-n' on huge files"
+    )]
+    pub skip: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["lines", "bytes", "in_range_order"],
+        help = "Output at most N lines, cheaper than '-n' on huge files"
+    )]
+    pub max_lines: Option<u64>,
+
+    #[arg(
+        long,
+        conflicts_with = "bytes",
+        help = "Output lines in order of range spec, repeated selections are repeated"
+    )]
+    pub in_range_order: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ViewFormat::Text,
+        conflicts_with_all = ["side_by_side", "follow"],
+        help = "Output format, hex dumps selected bytes or whole content like 'kf hex'"
+    )]
+    pub format: ViewFormat,
+
+    #[arg(
+        long,
+        value_name = "N|auto",
+        default_value_t = Columns::Count(hex::BYTES_PER_LINE),
+        value_parser = hex::parse_columns,
+        help = "Bytes per line of hex format, 'auto' fits terminal width"
+    )]
+    pub columns: Columns,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = hex::group_size_parser(),
+        help = "Bytes per group of hex format, groups are separated by an extra space"
+    )]
+    pub group_size: usize,
+
+    #[command(flatten)]
+    pub hex_style: hex::DumpStyleArgs,
+
+    #[arg(long, help = "View binary files as text anyway")]
+    pub force_text: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "bytes",
+        help = "Keep at most N bytes of each line, the rest of a longer line is skipped"
+    )]
+    pub max_line_bytes: Option<usize>,
+
+    #[arg(long, help = "List entries of directory arguments instead of failing")]
+    pub allow_dirs: bool,
+
+    #[arg(
+        long,
+        value_name = "MODE",
+        value_enum,
+        default_value_t = LineEnding::Preserve,
+        help = "Line endings of output lines"
+    )]
+    pub line_endings: LineEnding,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["bytes", "follow", "in_range_order", "squeeze_repeats"],
+        help = "Print two files next to each other in columns"
+    )]
+    pub side_by_side: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "bytes",
+        help = "Collapse runs of identical consecutive lines into one annotated line"
+    )]
+    pub squeeze_repeats: bool,
+
+    #[arg(
+        short = 'N',
+        long,
+        conflicts_with = "bytes",
+        help = "Prefix every output line with its line number"
+    )]
+    pub number: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "bytes",
+        help = "Prefix non-empty output lines with their line numbers"
+    )]
+    pub number_nonblank: bool,
+
+    #[arg(
+        long,
+        value_name = "SPEC",
+        conflicts_with = "bytes",
+        value_parser = clap::value_parser!(NumberFormat),
+        help = "Line number style, e.g. 'width=8,sep=:,zero,color', implies -N"
+    )]
+    pub number_format: Option<NumberFormat>,
+
+    #[arg(
+        long,
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = Paging::Auto,
+        help = "Pipe output through $PAGER or 'less'"
+    )]
+    pub paging: Paging,
+
+    #[arg(
+        short = 'f',
+        long,
+        help = "Output appended lines as the file grows, like 'tail -f', for a single file, not standard input"
+    )]
+    pub follow: bool,
+
+    #[arg(
+        long,
+        requires = "follow",
+        help = "Keep following file by name when it's rotated or recreated"
+    )]
+    pub retry: bool,
+
+    #[arg(
+        long,
+        requires = "file_paths",
+        conflicts_with = "follow",
+        help = "Clear screen and view files again whenever they change"
+    )]
+    pub watch: bool,
+
+    /// set from global `--color`
+    #[arg(skip)]
+    pub color: ColorWhen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ViewFormat {
+    Text,
+    Hex,
+}
+
+impl Default for ViewArgs {
+    /// Every option at its default, for commands like `kf head` built on top of view
+    fn default() -> Self {
+        ViewArgs::parse_from(["view"])
+    }
+}
+
+impl ViewArgs {
+    /// Line ranges of all `-n` occurrences merged into one spec
+    pub fn line_ranges(&self) -> Cow<'_, RangeSpec> {
+        match self.lines.as_slice() {
+            [] => Cow::Owned(RangeSpec::All),
+            [spec] => Cow::Borrowed(spec),
+            specs => {
+                let merged = specs
+                    .iter()
+                    .flat_map(|spec| match spec {
+                        RangeSpec::List(items) => items.clone(),
+                        spec => vec![spec.clone()],
+                    })
+                    .collect();
+                Cow::Owned(RangeSpec::List(merged))
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -58,7 +290,7 @@ impl TypedValueParser for RangeSpecValueParser {
             .to_str()
             .ok_or_else(|| clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))?;
 
-        value_str.parse().map_err(|err_msg| {
+        parse_range_spec(value_str).map_err(|err_msg| {
             let mut err = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
             if let Some(arg) = arg {
                 err.insert(
@@ -74,3 +306,33 @@ impl TypedValueParser for RangeSpecValueParser {
         })
     }
 }
+
+/// `@name` refers to a range preset from `[ranges]` table of config file, it's resolved after
+/// parsing by `RangeSpec::resolve_presets`
+fn parse_range_spec(value: &str) -> Result<RangeSpec, String> {
+    match value.strip_prefix('@') {
+        Some(name) => Ok(RangeSpec::Preset(name.to_string())),
+        None => value.parse().map_err(|e: ParseError| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_lines_merged() {
+        let args = ViewArgs::parse_from(["view", "-n", "1..3,5", "-n", "-2.."]);
+        assert_eq!(
+            *args.line_ranges(),
+            RangeSpec::List(vec![
+                RangeSpec::Range(1, 3),
+                RangeSpec::Single(5),
+                RangeSpec::From(-2),
+            ])
+        );
+
+        let args = ViewArgs::parse_from(["view"]);
+        assert_eq!(*args.line_ranges(), RangeSpec::All);
+    }
+}
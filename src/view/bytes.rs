@@ -0,0 +1,62 @@
+use std::io::{self, BufRead, Write};
+
+use super::error::Result;
+use super::range::{RangeCount, RangePos, RangeSpec};
+
+/// Outputs bytes inside range, byte positions start from 1 like line numbers.
+/// `total` is the input size when known, otherwise input is buffered for negative positions.
+pub fn view_reader_bytes<W: Write>(
+    reader: &mut impl BufRead,
+    total: Option<u64>,
+    spec: &RangeSpec,
+    writer: &mut W,
+) -> Result<()> {
+    match total {
+        None if spec.has_negative() => {
+            let mut content = vec![];
+            reader.read_to_end(&mut content)?;
+            let ranges = spec.normalize(content.len() as RangeCount);
+            write_selected_bytes(&mut content.as_slice(), &ranges, writer)?;
+        }
+        _ => {
+            let ranges = spec.normalize(total.unwrap_or(0) as RangeCount);
+            write_selected_bytes(reader, &ranges, writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_selected_bytes(
+    reader: &mut impl BufRead,
+    ranges: &RangeSpec,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let last_byte = ranges.last_line();
+    let mut pos: RangePos = 0;
+    let mut selected = vec![];
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        selected.clear();
+        for byte in buffer {
+            pos += 1;
+            if ranges.contains(pos) {
+                selected.push(*byte);
+            }
+        }
+        let len = buffer.len();
+        reader.consume(len);
+        writer.write_all(&selected)?;
+
+        if last_byte.is_some_and(|last| pos >= last) {
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+use liblzma::bufread::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// detects by file extension first, then by magic bytes at file start
+    fn detect(path: &Path, header: &[u8]) -> Option<Self> {
+        let by_extension = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Some(Compression::Gzip),
+            Some("zst") => Some(Compression::Zstd),
+            Some("xz") => Some(Compression::Xz),
+            _ => None,
+        };
+
+        by_extension.or_else(|| {
+            if header.starts_with(GZIP_MAGIC) {
+                Some(Compression::Gzip)
+            } else if header.starts_with(ZSTD_MAGIC) {
+                Some(Compression::Zstd)
+            } else if header.starts_with(XZ_MAGIC) {
+                Some(Compression::Xz)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Opens file for reading, compressed content is decompressed on the fly.
+/// Returns reader and size of content, which is unknown for compressed files.
+pub fn open_file(file_path: &Path) -> io::Result<(Box<dyn BufRead>, Option<u64>)> {
+    let f = File::open(file_path)?;
+    let size = f.metadata()?.len();
+    let mut reader = BufReader::new(f);
+
+    let compression = Compression::detect(file_path, reader.fill_buf()?);
+    log::debug!(
+        "opened {}, {} bytes, compression {:?}",
+        file_path.display(),
+        size,
+        compression
+    );
+    let reader: Box<dyn BufRead> = match compression {
+        None => return Ok((Box::new(reader), Some(size))),
+        Some(Compression::Gzip) => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Some(Compression::Zstd) => Box::new(BufReader::new(ZstdDecoder::with_buffer(reader)?)),
+        Some(Compression::Xz) => Box::new(BufReader::new(XzDecoder::new(reader))),
+    };
+
+    Ok((reader, None))
+}
@@ -0,0 +1,31 @@
+use std::{fs, io::Write, path::Path};
+
+use super::error::Result;
+
+/// Lists directory entries sorted by name with sizes and type indicators like `ls -F`
+pub fn view_dir<W: Write>(dir_path: &Path, writer: &mut W) -> Result<()> {
+    let mut entries = fs::read_dir(dir_path)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_type = entry.file_type()?;
+        let indicator = if file_type.is_dir() {
+            "/"
+        } else if file_type.is_symlink() {
+            "@"
+        } else {
+            ""
+        };
+        let size = entry.metadata().map_or(0, |metadata| metadata.len());
+
+        writeln!(
+            writer,
+            "{:>10}  {}{}",
+            size,
+            entry.file_name().to_string_lossy(),
+            indicator
+        )?;
+    }
+
+    Ok(())
+}
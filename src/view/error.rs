@@ -1,9 +1,53 @@
+use std::io;
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+use crate::exit::{self, ExitCode};
+
 #[derive(Error, Debug)]
 pub enum ViewError {
     #[error("{0}")]
-    IoError(#[from] std::io::Error),
+    IoError(#[from] io::Error),
+
+    #[error("input looks like a binary file, use 'kf hex' to dump it or --force-text to view it")]
+    BinaryFile,
+
+    #[error("{} is a directory, use --allow-dirs to list it", .0.display())]
+    IsDirectory(PathBuf),
+
+    #[error("--follow requires exactly one file")]
+    FollowRequiresSingleFile,
+
+    #[error("--side-by-side requires exactly two files")]
+    SideBySideRequiresTwoFiles,
+
+    #[error("{} of the files could not be viewed", .failed.len())]
+    Partial { failed: Vec<(PathBuf, io::Error)> },
+}
+
+impl ViewError {
+    /// Converts to `io::Error`, keeping original error of `IoError` variant
+    pub fn into_io_error(self) -> io::Error {
+        match self {
+            ViewError::IoError(e) => e,
+            e => io::Error::other(e),
+        }
+    }
+}
+
+impl ExitCode for ViewError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            ViewError::FollowRequiresSingleFile | ViewError::SideBySideRequiresTwoFiles => {
+                exit::USAGE
+            }
+            ViewError::IoError(_)
+            | ViewError::BinaryFile
+            | ViewError::IsDirectory(_)
+            | ViewError::Partial { .. } => exit::IO,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ViewError>;
@@ -0,0 +1,84 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use super::error::Result;
+use crate::cancel;
+
+/// interval between checks for appended content after reaching EOF
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Prints lines appended to file forever from current position of `reader`, like `tail -f`.
+/// Lines are written as bytes, in any encoding. With `retry` the file is followed by name, so
+/// it's reopened when rotated or recreated.
+pub fn follow_file<W: Write>(
+    file_path: &Path,
+    mut reader: BufReader<File>,
+    retry: bool,
+    writer: &mut W,
+) -> Result<()> {
+    let mut pos = reader.stream_position()?;
+    let mut identity = file_identity(&reader.get_ref().metadata()?);
+    // partial line without line break is kept until it's completed
+    let mut buffer = vec![];
+
+    loop {
+        cancel::check()?;
+        let n = reader.read_until(b'\n', &mut buffer)?;
+        if n > 0 {
+            pos += n as u64;
+            if buffer.ends_with(b"\n") {
+                writer.write_all(&buffer)?;
+                writer.flush()?;
+                buffer.clear();
+            }
+            continue;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+
+        if retry {
+            match fs::metadata(file_path) {
+                Ok(metadata) if file_identity(&metadata) != identity => {
+                    eprintln!(
+                        "{} has been replaced, following new file",
+                        file_path.display()
+                    );
+                    let Ok(file) = File::open(file_path) else {
+                        continue;
+                    };
+                    identity = file_identity(&file.metadata()?);
+                    reader = BufReader::new(file);
+                    buffer.clear();
+                    pos = 0;
+                    continue;
+                }
+                Ok(_) => {}
+                // file is missing during rotation, wait for it to appear again
+                Err(_) => continue,
+            }
+        }
+
+        if reader.get_ref().metadata()?.len() < pos {
+            eprintln!("{} truncated", file_path.display());
+            pos = reader.seek(SeekFrom::Start(0))?;
+            buffer.clear();
+        }
+    }
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
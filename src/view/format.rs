@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+
+use super::args::ViewArgs;
+use super::gutter::Gutter;
+use super::range::{RangeCount, RangePos, RangeSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum LineEnding {
+    /// Keep original line endings, a missing trailing newline stays missing
+    Preserve,
+    /// Terminate every line with '\n'
+    Lf,
+    /// Terminate every line with '\r\n'
+    Crlf,
+}
+
+/// Formats selected lines before they are written to output
+pub struct LineFormat<'a> {
+    gutter: Option<Gutter>,
+    chars: Option<&'a RangeSpec>,
+    line_ending: LineEnding,
+    squeeze_repeats: bool,
+    /// first line of current run of identical lines and its line number, held by `--squeeze-repeats`
+    pending: Option<(String, RangePos)>,
+    repeats: usize,
+}
+
+impl<'a> LineFormat<'a> {
+    /// `max_line` is the highest line number that may be printed, `None` when unknown
+    pub fn new(args: &'a ViewArgs, max_line: Option<RangePos>) -> Self {
+        LineFormat {
+            gutter: Gutter::from_args(args, max_line),
+            chars: args.chars.as_ref(),
+            line_ending: args.line_endings,
+            squeeze_repeats: args.squeeze_repeats,
+            pending: None,
+            repeats: 0,
+        }
+    }
+
+    pub fn write_line<W: Write>(
+        &mut self,
+        line: &str,
+        line_no: RangePos,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        if !self.squeeze_repeats {
+            return self.render(line, line_no, None, writer);
+        }
+
+        match &self.pending {
+            Some((pending, _)) if split_line_ending(pending).0 == split_line_ending(line).0 => {
+                self.repeats += 1;
+                Ok(())
+            }
+            _ => {
+                self.finish(writer)?;
+                self.pending = Some((line.to_string(), line_no));
+                self.repeats = 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes line held back by `--squeeze-repeats`, called after last line
+    pub fn finish<W: Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self.pending.take() {
+            Some((line, line_no)) => {
+                let repeats = (self.repeats > 1).then_some(self.repeats);
+                self.render(&line, line_no, repeats, writer)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn render<W: Write>(
+        &self,
+        line: &str,
+        line_no: RangePos,
+        repeats: Option<usize>,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let (line, original_ending) = split_line_ending(line);
+        let line = match self.chars {
+            Some(chars) => select_chars(line, chars),
+            None => Cow::Borrowed(line),
+        };
+        let ending = match self.line_ending {
+            LineEnding::Preserve => original_ending,
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        };
+
+        if let Some(gutter) = &self.gutter {
+            write!(writer, "{}", gutter.format(line_no, &line))?;
+        }
+        write!(writer, "{}", line)?;
+        if let Some(repeats) = repeats {
+            write!(writer, " (repeated {} times)", repeats)?;
+        }
+        write!(writer, "{}", ending)
+    }
+}
+
+/// Splits line into content and its terminator, which is empty for last line without newline
+fn split_line_ending(line: &str) -> (&str, &str) {
+    if let Some(content) = line.strip_suffix("\r\n") {
+        (content, "\r\n")
+    } else if let Some(content) = line.strip_suffix('\n') {
+        (content, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// Characters of line inside range, positions start from 1 and negative ones count from line end
+fn select_chars<'b>(line: &'b str, spec: &RangeSpec) -> Cow<'b, str> {
+    if *spec == RangeSpec::All {
+        return Cow::Borrowed(line);
+    }
+
+    let total = line.chars().count();
+    let ranges = spec.normalize(total as RangeCount);
+
+    line.chars()
+        .enumerate()
+        .filter(|(i, _)| ranges.contains((i + 1) as RangePos))
+        .map(|(_, c)| c)
+        .collect::<String>()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_line_ending() {
+        assert_eq!(split_line_ending("abc\r\n"), ("abc", "\r\n"));
+        assert_eq!(split_line_ending("abc\n"), ("abc", "\n"));
+        assert_eq!(split_line_ending("abc"), ("abc", ""));
+    }
+
+    #[test]
+    fn test_select_chars() {
+        let spec = "2..4".parse::<RangeSpec>().unwrap();
+        assert_eq!(select_chars("abcdef", &spec), "bcd");
+        assert_eq!(select_chars("日本語テキスト", &spec), "本語テ");
+
+        let spec = "-3..".parse::<RangeSpec>().unwrap();
+        assert_eq!(select_chars("abcdef", &spec), "def");
+        assert_eq!(select_chars("ab", &spec), "ab");
+    }
+
+    #[test]
+    fn test_squeeze_repeats() {
+        use clap::Parser;
+
+        let args = ViewArgs::parse_from(["view", "--squeeze-repeats"]);
+        let mut format = LineFormat::new(&args, None);
+        let mut output = vec![];
+        for (i, line) in ["a\n", "b\n", "b\n", "b\n", "c"].iter().enumerate() {
+            format.write_line(line, i as RangePos + 1, &mut output).unwrap();
+        }
+        format.finish(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a\nb (repeated 3 times)\nc"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use colored::Colorize;
+
+use super::args::ViewArgs;
+use super::range::RangePos;
+
+/// width used when the highest line number isn't known in advance, same as `cat -n`
+const DEFAULT_WIDTH: usize = 6;
+
+/// separator between line number and line content
+const DEFAULT_SEPARATOR: &str = "  ";
+
+/// Style of line number column, parsed from `--number-format`
+/// like `width=8,sep=:,zero,color`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    /// fixed width, computed from highest line number when `None`
+    width: Option<usize>,
+    separator: String,
+    zero_pad: bool,
+    color: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            width: None,
+            separator: DEFAULT_SEPARATOR.to_string(),
+            zero_pad: false,
+            color: false,
+        }
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut format = NumberFormat::default();
+
+        for item in s.split(',').filter(|item| !item.is_empty()) {
+            match item.split_once('=') {
+                Some(("width", width)) => {
+                    let width = width.parse().map_err(|_| format!("invalid width '{}'", width))?;
+                    format.width = Some(width);
+                }
+                Some(("sep", separator)) => format.separator = separator.replace("\\t", "\t"),
+                None if item == "zero" => format.zero_pad = true,
+                None if item == "color" => format.color = true,
+                _ => {
+                    return Err(format!(
+                        "unknown item '{}', expected width=N, sep=S, zero or color",
+                        item
+                    ));
+                }
+            }
+        }
+
+        Ok(format)
+    }
+}
+
+/// Line number column printed before each output line
+pub struct Gutter {
+    width: usize,
+    nonblank_only: bool,
+    separator: String,
+    zero_pad: bool,
+    left_align: bool,
+    color: bool,
+}
+
+impl Gutter {
+    /// `max_line` is the highest line number that may be printed, `None` when unknown
+    pub fn from_args(args: &ViewArgs, max_line: Option<RangePos>) -> Option<Self> {
+        if !args.number && !args.number_nonblank && args.number_format.is_none() {
+            return None;
+        }
+
+        let format = args.number_format.clone().unwrap_or_default();
+        let width = format.width.unwrap_or_else(|| {
+            max_line.map_or(DEFAULT_WIDTH, |line| line.max(1).to_string().len())
+        });
+        Some(Gutter {
+            width,
+            nonblank_only: args.number_nonblank,
+            separator: format.separator,
+            zero_pad: format.zero_pad,
+            left_align: false,
+            color: format.color,
+        })
+    }
+
+    /// Gutter of `kf nl`, which decides itself which lines get a number
+    pub fn new(width: usize, separator: String, zero_pad: bool, left_align: bool) -> Self {
+        Gutter {
+            width,
+            nonblank_only: false,
+            separator,
+            zero_pad,
+            left_align,
+            color: false,
+        }
+    }
+
+    pub fn format(&self, line_no: RangePos, line: &str) -> String {
+        if self.nonblank_only && line.trim_end_matches(['\r', '\n']).is_empty() {
+            return String::new();
+        }
+        self.number(line_no)
+    }
+
+    /// Gutter as wide as a numbered one, keeps unnumbered lines aligned
+    pub fn blank(&self) -> String {
+        " ".repeat(self.width + self.separator.chars().count())
+    }
+
+    /// Number of line with separator, blank lines included
+    pub fn number(&self, line_no: RangePos) -> String {
+        let number = if self.left_align {
+            format!("{:<width$}", line_no, width = self.width)
+        } else if self.zero_pad {
+            format!("{:0>width$}", line_no, width = self.width)
+        } else {
+            format!("{:>width$}", line_no, width = self.width)
+        };
+        if self.color {
+            format!("{}{}", number.green(), self.separator)
+        } else {
+            format!("{}{}", number, self.separator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number_format() {
+        assert_eq!("".parse::<NumberFormat>().unwrap(), NumberFormat::default());
+        assert_eq!(
+            "width=4,sep=:,zero".parse::<NumberFormat>().unwrap(),
+            NumberFormat {
+                width: Some(4),
+                separator: ":".to_string(),
+                zero_pad: true,
+                color: false,
+            }
+        );
+        assert_eq!("sep=\\t".parse::<NumberFormat>().unwrap().separator, "\t");
+        assert!("width=x".parse::<NumberFormat>().is_err());
+        assert!("bold".parse::<NumberFormat>().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{self, BufRead};
+
+/// Reads lines lossily, with optional cap on bytes kept from each line
+pub struct LineReader<'a, R: BufRead> {
+    reader: &'a mut R,
+    max_line_bytes: Option<usize>,
+    truncated: usize,
+}
+
+impl<'a, R: BufRead> LineReader<'a, R> {
+    pub fn new(reader: &'a mut R, max_line_bytes: Option<usize>) -> Self {
+        Self {
+            reader,
+            max_line_bytes,
+            truncated: 0,
+        }
+    }
+
+    /// Like `BufRead::read_line`, but invalid UTF-8 is replaced with U+FFFD instead of being an
+    /// error, and bytes beyond the limit are skipped without being buffered.
+    /// Returns count of bytes consumed from reader.
+    pub fn read_line(&mut self, buffer: &mut String) -> io::Result<usize> {
+        let mut bytes = vec![];
+        let mut consumed = 0;
+        let mut truncated = false;
+
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                break;
+            }
+
+            let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (&available[..=i], true),
+                None => (available, false),
+            };
+            let len = chunk.len();
+            // line terminator doesn't count against the limit
+            let content = len - usize::from(done);
+            let room = self
+                .max_line_bytes
+                .map_or(content, |max| max.saturating_sub(bytes.len()).min(content));
+            truncated |= room < content;
+            bytes.extend_from_slice(&chunk[..room]);
+
+            self.reader.consume(len);
+            consumed += len;
+            if done {
+                bytes.push(b'\n');
+                break;
+            }
+        }
+
+        if truncated {
+            self.truncated += 1;
+        }
+        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+        Ok(consumed)
+    }
+
+    pub fn read_all_lines(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        let mut buffer = String::new();
+
+        while self.read_line(&mut buffer)? > 0 {
+            lines.push(buffer.clone());
+            buffer.clear();
+        }
+
+        Ok(lines)
+    }
+
+    /// tells on stderr how many lines were cut by the limit, if any
+    pub fn report_truncation(&self) {
+        if let Some(max) = self.max_line_bytes
+            && self.truncated > 0
+        {
+            eprintln!("{} line(s) truncated to {} bytes", self.truncated, max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_line_truncated() {
+        let mut input = "abcdefgh\nabcd\nklmnop".as_bytes();
+        let mut reader = LineReader::new(&mut input, Some(4));
+        let mut buffer = String::new();
+
+        assert_eq!(reader.read_line(&mut buffer).unwrap(), 9);
+        assert_eq!(buffer, "abcd\n");
+        buffer.clear();
+        assert_eq!(reader.read_line(&mut buffer).unwrap(), 5);
+        assert_eq!(buffer, "abcd\n");
+        buffer.clear();
+        assert_eq!(reader.read_line(&mut buffer).unwrap(), 6);
+        assert_eq!(buffer, "klmn");
+        buffer.clear();
+        assert_eq!(reader.read_line(&mut buffer).unwrap(), 0);
+        assert_eq!(reader.truncated, 2);
+    }
+
+    #[test]
+    fn test_read_line_lossy() {
+        let mut input: &[u8] = b"a\xffb\n";
+        let lines = LineReader::new(&mut input, None).read_all_lines().unwrap();
+        assert_eq!(lines, vec!["a\u{fffd}b\n"]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use super::decompress;
+
+/// bytes inspected to guess text encoding
+const ENCODING_SAMPLE_SIZE: usize = 8192;
+
+/// Facts about a file shown in header by `--verbose`
+pub struct FileMeta {
+    size: u64,
+    modified: Option<SystemTime>,
+    encoding: &'static str,
+    lines: u64,
+}
+
+impl FileMeta {
+    /// Reads metadata and scans (decompressed) content to count lines
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let (mut reader, _) = decompress::open_file(path)?;
+        let encoding = detect_encoding(reader.fill_buf()?);
+
+        let mut lines = 0;
+        let mut last_byte = None;
+        loop {
+            let buffer = reader.fill_buf()?;
+            let Some(&last) = buffer.last() else {
+                break;
+            };
+            lines += buffer.iter().filter(|&&b| b == b'\n').count() as u64;
+            last_byte = Some(last);
+            let len = buffer.len();
+            reader.consume(len);
+        }
+        // last line without terminator counts too
+        if last_byte.is_some_and(|b| b != b'\n') {
+            lines += 1;
+        }
+
+        Ok(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            encoding,
+            lines,
+        })
+    }
+}
+
+impl fmt::Display for FileMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, ", human_size(self.size))?;
+        if let Some(age) = self.modified.and_then(|m| m.elapsed().ok()) {
+            write!(f, "modified {} ago, ", human_duration(age))?;
+        }
+        write!(f, "{}, {} lines", self.encoding, self.lines)
+    }
+}
+
+fn detect_encoding(header: &[u8]) -> &'static str {
+    let sample = &header[..header.len().min(ENCODING_SAMPLE_SIZE)];
+
+    if sample.starts_with(&[0xef, 0xbb, 0xbf]) {
+        "UTF-8 with BOM"
+    } else if sample.starts_with(&[0xff, 0xfe]) {
+        "UTF-16LE"
+    } else if sample.starts_with(&[0xfe, 0xff]) {
+        "UTF-16BE"
+    } else if sample.contains(&0) {
+        "binary"
+    } else if sample.is_ascii() {
+        "ASCII"
+    } else {
+        match std::str::from_utf8(sample) {
+            Ok(_) => "UTF-8",
+            // sample may end in the middle of a multibyte character
+            Err(e) if e.error_len().is_none() => "UTF-8",
+            Err(_) => "unknown encoding",
+        }
+    }
+}
+
+fn human_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn human_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"plain"), "ASCII");
+        assert_eq!(detect_encoding("中文".as_bytes()), "UTF-8");
+        assert_eq!(detect_encoding(&"中文".as_bytes()[..4]), "UTF-8");
+        assert_eq!(detect_encoding(&[0xff, 0xfe, b'a', 0]), "UTF-16LE");
+        assert_eq!(detect_encoding(b"a\xffb"), "unknown encoding");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
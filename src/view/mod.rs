@@ -1,107 +1,571 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader, IsTerminal};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IsTerminal, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Instant;
 
 mod args;
+mod bytes;
+mod decompress;
+mod dir;
 mod error;
+mod follow;
+mod format;
+mod gutter;
+mod lines;
+mod meta;
+mod pager;
 mod range;
+mod side;
+mod watch;
 
-pub use args::ViewArgs;
+use crate::hex::{HexRenderer, HexWriter};
+use crate::paths;
+use crate::report;
+pub use args::{ViewArgs, ViewFormat};
 pub use error::{Result, ViewError};
-use range::{RangeCount, RangePos};
+pub use format::LineEnding;
+use format::LineFormat;
+pub use gutter::Gutter;
+use lines::LineReader;
+use pager::Pager;
+pub use pager::Paging;
+pub use range::{RangeCount, RangePos, RangeSpec};
 
+/// files up to this size are rendered ahead into memory while viewing multiple files
+const PRELOAD_MAX_SIZE: u64 = 1 << 20;
+
+/// Views files on stdout, or through a pager when enabled
 pub fn view_files(args: ViewArgs) -> Result<()> {
-    match args.file_paths.len() {
-        0 => view_stdin(&args)?,
-        1 => view_single_file(&args)?,
-        _ => view_multiple_files(&args)?,
+    // colored checks terminal on its own, global --color choice has to override it
+    colored::control::set_override(args.color.enabled());
+
+    if args.watch {
+        return watch::watch_files(&args, &mut io::stdout().lock());
+    }
+
+    // endless output of follow mode and interactive echo aren't paged
+    let interactive = args.file_paths.is_empty() && io::stdin().is_terminal();
+    let pager = if args.follow || interactive {
+        None
+    } else {
+        Pager::spawn(args.paging)
     };
 
-    Ok(())
+    match pager {
+        Some(mut pager) => {
+            let result = view_files_to(&args, pager.writer());
+            pager.wait()?;
+            result
+        }
+        None => view_files_to(&args, &mut io::stdout().lock()),
+    }
 }
 
-fn view_stdin(args: &ViewArgs) -> Result<()> {
+/// Views files to given writer, paging option is ignored
+pub fn view_files_to<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
+    let start = Instant::now();
+    if args.follow && args.file_paths.len() != 1 {
+        return Err(ViewError::FollowRequiresSingleFile);
+    }
+    if args.side_by_side {
+        side::view_side_by_side(args, writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    let result = match args.file_paths.len() {
+        0 => {
+            if args.always_header || args.verbose {
+                output_header(Path::new("-"), args, writer)?;
+            }
+            view_stdin(args, writer)
+        }
+        1 => {
+            if args.always_header || args.verbose {
+                output_header(&args.file_paths[0], args, writer)?;
+            }
+            view_single_file(args, writer)
+        }
+        _ => view_multiple_files(args, writer),
+    };
+    // output of succeeded files is flushed even when others failed
+    writer.flush()?;
+    log::info!(
+        "viewed {} files in {:?}",
+        args.file_paths.len(),
+        start.elapsed()
+    );
+
+    result
+}
+
+fn view_stdin<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
     let mut reader = std::io::stdin().lock();
     if reader.is_terminal() {
-        view_interactive_stdin(&mut reader)
+        view_interactive_stdin(&mut reader, args, writer)
     } else {
-        view_piped_stdin(&mut reader, args)
+        view_piped_stdin(&mut reader, args, writer)
     }
 }
 
-fn view_interactive_stdin(reader: &mut impl BufRead) -> Result<()> {
+/// ring buffer of tail ranges allocates at most this many slots in advance
+const TAIL_PREALLOCATE_LIMIT: usize = 4096;
+
+/// sentinel line that ends interactive input like end of file does
+const QUIT_COMMAND: &str = ".quit";
+
+/// Echoes typed lines back, numbered with `-N` and prompted with next line number on stderr
+fn view_interactive_stdin<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let mut format = LineFormat::new(args, None);
+    let numbered = args.number || args.number_nonblank || args.number_format.is_some();
     // reuse single String buffer in every loop iteration
     let mut buffer = String::new();
+    let mut line_no: RangePos = 0;
 
-    while reader.read_line(&mut buffer)? > 0 {
-        let line = buffer.trim_end();
-        println!("{}", line);
+    loop {
+        if numbered {
+            eprint!("{}> ", line_no + 1);
+        }
+        if reader.read_line(&mut buffer)? == 0 {
+            break;
+        }
+
+        if buffer.trim_end_matches(['\r', '\n']) == QUIT_COMMAND {
+            break;
+        }
+        line_no += 1;
+        format.write_line(&buffer, line_no, writer)?;
+        writer.flush()?;
         buffer.clear();
     }
 
+    format.finish(writer)?;
+
     Ok(())
 }
 
-fn view_piped_stdin(reader: &mut impl BufRead, args: &ViewArgs) -> Result<()> {
-    view_reader_text(reader, args)
+fn view_piped_stdin<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    view_reader(reader, None, args, writer)
 }
 
-fn view_single_file(args: &ViewArgs) -> Result<()> {
+fn view_single_file<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
     let file_path = &args.file_paths[0];
-    view_single_file_by_path(file_path, args)
+    if !args.follow {
+        return view_path(file_path, args, writer);
+    }
+
+    // followed through the same handle, lines appended while viewing aren't missed
+    let file = File::open(file_path)?;
+    let size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    view_reader(&mut reader, Some(size), args, writer)?;
+    writer.flush()?;
+    // content present when view started was viewed even if ranges ended early
+    let viewed = reader.stream_position()?.max(size);
+    reader.seek(SeekFrom::Start(viewed))?;
+    follow::follow_file(file_path, reader, args.retry, writer)
 }
 
-fn view_single_file_by_path(file_path: &Path, args: &ViewArgs) -> Result<()> {
-    let f = File::open(file_path)?;
-    let mut reader = BufReader::new(f);
+/// path "-" stands for standard input
+fn is_stdin_path(file_path: &Path) -> bool {
+    file_path == Path::new("-")
+}
 
-    view_reader_text(&mut reader, args)
+fn display_path(file_path: &Path) -> Cow<'_, str> {
+    if is_stdin_path(file_path) {
+        Cow::Borrowed("standard input")
+    } else {
+        paths::native(file_path)
+    }
 }
 
-fn output_file_separator() {
-    println!("")
+fn view_path<W: Write>(file_path: &Path, args: &ViewArgs, writer: &mut W) -> Result<()> {
+    if is_stdin_path(file_path) {
+        view_piped_stdin(&mut io::stdin().lock(), args, writer)
+    } else if file_path.is_dir() {
+        if args.allow_dirs {
+            dir::view_dir(file_path, writer)
+        } else {
+            Err(ViewError::IsDirectory(file_path.to_path_buf()))
+        }
+    } else {
+        view_single_file_by_path(file_path, args, writer)
+    }
+}
+
+fn view_single_file_by_path<W: Write>(
+    file_path: &Path,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let (mut reader, size) = decompress::open_file(file_path)?;
+    view_reader(&mut reader, size, args, writer)
+}
+
+/// `total` is the input size when known
+fn view_reader<W: Write>(
+    reader: &mut impl BufRead,
+    total: Option<u64>,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    match (args.format, &args.bytes) {
+        (ViewFormat::Hex, bytes) => {
+            let renderer = HexRenderer::new(
+                args.columns,
+                args.group_size,
+                &args.hex_style,
+                args.color.enabled(),
+            );
+            let mut hex_writer = HexWriter::new(renderer, writer);
+            let bytes = bytes.as_ref().unwrap_or(&RangeSpec::All);
+            bytes::view_reader_bytes(reader, total, bytes, &mut hex_writer)?;
+            hex_writer.finish()?;
+            Ok(())
+        }
+        (ViewFormat::Text, Some(bytes)) => bytes::view_reader_bytes(reader, total, bytes, writer),
+        (ViewFormat::Text, None) => view_reader_text(reader, args, writer),
+    }
+}
+
+fn output_header<W: Write>(file_path: &Path, args: &ViewArgs, writer: &mut W) -> Result<()> {
+    let header = args.header_format.replace("{path}", &display_path(file_path));
+    write!(writer, "{}", header)?;
+
+    // failure to read file is reported when viewing it
+    if args.verbose
+        && file_path.is_file()
+        && let Ok(meta) = meta::FileMeta::read(file_path)
+    {
+        write!(writer, " ({})", meta)?;
+    }
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+fn output_file_separator<W: Write>(writer: &mut W) -> Result<()> {
+    writeln!(writer)?;
+    Ok(())
 }
 
-fn view_multiple_files(args: &ViewArgs) -> Result<()> {
-    for (i, file_path) in args.file_paths.iter().enumerate() {
+/// Views every file even when some of them fail, failures are reported together at the end.
+/// Small files following the current one are rendered on background threads, output order is
+/// kept. Larger files are streamed in place, so memory use doesn't grow with file size.
+fn view_multiple_files<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
+    let stop = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let result = view_preloading(args, writer, scope, &stop);
+        // scope waits for preloads still running, they give up on their next write
+        stop.store(true, Ordering::Relaxed);
+        result
+    })
+}
+
+fn view_preloading<'scope, 'env, W: Write>(
+    args: &'env ViewArgs,
+    writer: &mut W,
+    scope: &'scope thread::Scope<'scope, 'env>,
+    stop: &'env AtomicBool,
+) -> Result<()> {
+    let preload_count = thread::available_parallelism().map_or(1, usize::from);
+    let file_paths = &args.file_paths;
+    let mut failed = vec![];
+    let mut preloaded = VecDeque::new();
+
+    for (i, file_path) in file_paths.iter().enumerate() {
+        // keep up to `preload_count` files ahead rendering, stdin can only be read in place
+        while preloaded.len() <= preload_count && i + preloaded.len() < file_paths.len() {
+            let next_path = &file_paths[i + preloaded.len()];
+            let handle = is_preloadable(next_path)
+                .then(|| scope.spawn(move || render_path(next_path, args, stop)));
+            preloaded.push_back(handle);
+        }
+
         if !args.quite {
             if i > 0 {
-                output_file_separator();
+                output_file_separator(writer)?;
             }
 
-            println!("==> {} <==", file_path.display());
+            output_header(file_path, args, writer)?;
         }
 
-        if let Err(e) = view_single_file_by_path(file_path, args) {
-            eprintln!("view file error: {}", e);
+        let result = match preloaded.pop_front().flatten() {
+            Some(handle) => {
+                let (output, result) = handle.join().expect("preload thread panicked");
+                writer.write_all(&output)?;
+                result
+            }
+            None => view_path(file_path, args, writer),
+        };
+
+        match result {
+            Err(ViewError::IoError(e)) if e.kind() == io::ErrorKind::BrokenPipe => {
+                return Err(e.into());
+            }
+            Err(e) => {
+                report::file_error("view", format_args!("{}: {}", file_path.display(), e));
+                failed.push((file_path.clone(), e.into_io_error()));
+            }
+            Ok(_) => {}
         }
     }
 
-    Ok(())
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(ViewError::Partial { failed })
+    }
+}
+
+/// Regular files small enough to be rendered into memory ahead of their turn
+fn is_preloadable(file_path: &Path) -> bool {
+    !is_stdin_path(file_path)
+        && fs::metadata(file_path)
+            .is_ok_and(|meta| meta.is_file() && meta.len() <= PRELOAD_MAX_SIZE)
+}
+
+/// Renders file into memory, output produced before an error is kept. Rendering stops once
+/// `stop` is set, when output of the file isn't wanted anymore.
+fn render_path(file_path: &Path, args: &ViewArgs, stop: &AtomicBool) -> (Vec<u8>, Result<()>) {
+    let mut output = StoppableWriter {
+        buffer: vec![],
+        stop,
+    };
+    let result = view_path(file_path, args, &mut output);
+    (output.buffer, result)
+}
+
+/// In-memory output failing once its render is stopped
+struct StoppableWriter<'a> {
+    buffer: Vec<u8>,
+    stop: &'a AtomicBool,
 }
 
-fn view_reader_text(reader: &mut impl BufRead, args: &ViewArgs) -> Result<()> {
-    let lines = read_all_lines(reader)?;
-    let ranges = args.lines.normalize(lines.len() as RangeCount);
+impl Write for StoppableWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "view stopped"));
+        }
+        self.buffer.write(buf)
+    }
 
-    lines.iter().enumerate().for_each(|(i, line)| {
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn view_reader_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    // control characters of binary content may corrupt terminal
+    if !args.force_text && is_binary(reader.fill_buf()?) {
+        return Err(ViewError::BinaryFile);
+    }
+
+    if args.skip.is_some() || args.max_lines.is_some() {
+        view_window_text(reader, args, writer)
+    } else if args.in_range_order {
+        view_ordered_text(reader, args, writer)
+    } else if let Some(window) = args.line_ranges().tail_window() {
+        view_tail_text(reader, args, window, writer)
+    } else if args.line_ranges().has_negative() {
+        view_buffered_text(reader, args, writer)
+    } else {
+        view_streaming_text(reader, args, writer)
+    }
+}
+
+/// positions relative to the end need total line count, so whole input is buffered
+fn view_buffered_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let mut line_reader = LineReader::new(reader, args.max_line_bytes);
+    let lines = line_reader.read_all_lines()?;
+    line_reader.report_truncation();
+    let total = lines.len() as RangePos;
+    let line_ranges = args.line_ranges();
+    let ranges = line_ranges.normalize(total as RangeCount);
+    let max_line = ranges.last_line().map_or(total, |last| last.min(total));
+    let mut format = LineFormat::new(args, Some(max_line));
+
+    for (i, line) in lines.iter().enumerate() {
         let line_no = (i + 1) as RangePos;
         if ranges.contains(line_no) {
-            println!("{}", line);
+            format.write_line(line, line_no, writer)?;
         }
-    });
+    }
+
+    format.finish(writer)?;
 
     Ok(())
 }
 
-fn read_all_lines<R: BufRead>(reader: &mut R) -> Result<Vec<String>> {
-    let mut lines = Vec::new();
+/// range selects only among last `window` lines, so just those are kept in a ring buffer
+fn view_tail_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    window: RangeCount,
+    writer: &mut W,
+) -> Result<()> {
+    let capacity = usize::try_from(window).unwrap_or(usize::MAX);
+    let mut tail = VecDeque::with_capacity(capacity.min(TAIL_PREALLOCATE_LIMIT));
+    let mut total: RangeCount = 0;
     let mut buffer = String::new();
+    let mut line_reader = LineReader::new(reader, args.max_line_bytes);
+
+    while line_reader.read_line(&mut buffer)? > 0 {
+        total += 1;
+        if tail.len() == capacity {
+            // recycle allocation of dropped line
+            let mut oldest: String = tail.pop_front().unwrap_or_default();
+            oldest.clear();
+            tail.push_back(std::mem::replace(&mut buffer, oldest));
+        } else {
+            tail.push_back(std::mem::take(&mut buffer));
+        }
+    }
+    line_reader.report_truncation();
+
+    let line_ranges = args.line_ranges();
+    let ranges = line_ranges.normalize(total);
+    let first_line = (total - tail.len() as RangeCount + 1) as RangePos;
+    let max_line = ranges.last_line().map_or(total as RangePos, |last| last.min(total as RangePos));
+    let mut format = LineFormat::new(args, Some(max_line));
+
+    for (line_no, line) in (first_line..).zip(&tail) {
+        if ranges.contains(line_no) {
+            format.write_line(line, line_no, writer)?;
+        }
+    }
+
+    format.finish(writer)?;
+
+    Ok(())
+}
+
+/// outputs lines in order of range spec instead of file order, so whole input is buffered
+fn view_ordered_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let mut line_reader = LineReader::new(reader, args.max_line_bytes);
+    let lines = line_reader.read_all_lines()?;
+    line_reader.report_truncation();
+    let line_ranges = args.line_ranges();
+    let ranges = line_ranges.normalize(lines.len() as RangeCount);
+    let positions = ranges.ordered_positions(lines.len() as RangeCount);
+    let mut format = LineFormat::new(args, positions.iter().max().copied());
+
+    for line_no in positions {
+        format.write_line(&lines[line_no as usize - 1], line_no, writer)?;
+    }
+
+    format.finish(writer)?;
 
-    while reader.read_line(&mut buffer)? > 0 {
-        lines.push(buffer.clone());
+    Ok(())
+}
+
+/// reads line by line with constant memory, stops after last line in range
+fn view_streaming_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let line_ranges = args.line_ranges();
+    let last_line = line_ranges.last_line();
+    let mut format = LineFormat::new(args, last_line);
+    // reuse single String buffer in every loop iteration
+    let mut buffer = String::new();
+    let mut line_no: RangePos = 0;
+    let mut line_reader = LineReader::new(reader, args.max_line_bytes);
+
+    while line_reader.read_line(&mut buffer)? > 0 {
+        line_no += 1;
+        if line_ranges.contains(line_no) {
+            format.write_line(&buffer, line_no, writer)?;
+        }
+        buffer.clear();
+
+        if last_line.is_some_and(|last| line_no >= last) {
+            break;
+        }
+    }
+    line_reader.report_truncation();
+
+    format.finish(writer)?;
+
+    Ok(())
+}
+
+/// outputs lines after `--skip` up to `--max-lines` without any range normalization
+fn view_window_text<W: Write>(
+    reader: &mut impl BufRead,
+    args: &ViewArgs,
+    writer: &mut W,
+) -> Result<()> {
+    let skip = args.skip.unwrap_or(0);
+    let last_line = args.max_lines.map(|max| (skip + max) as RangePos);
+    let mut format = LineFormat::new(args, last_line);
+    // reuse single String buffer in every loop iteration
+    let mut buffer = String::new();
+    let mut line_no: RangePos = 0;
+    let mut line_reader = LineReader::new(reader, args.max_line_bytes);
+
+    while last_line.is_none_or(|last| line_no < last) && line_reader.read_line(&mut buffer)? > 0 {
+        line_no += 1;
+        if line_no as RangeCount > skip {
+            format.write_line(&buffer, line_no, writer)?;
+        }
         buffer.clear();
     }
+    line_reader.report_truncation();
+
+    format.finish(writer)?;
+
+    Ok(())
+}
 
-    Ok(lines)
+/// content is considered binary when NUL byte appears in its beginning
+fn is_binary(header: &[u8]) -> bool {
+    header.contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_view_files_to_writer() {
+        let file_path = std::env::temp_dir().join("kf_view_files_to_writer.txt");
+        fs::write(&file_path, "one\ntwo\nthree\n").unwrap();
+
+        let args = ViewArgs::parse_from(["view", "-N", "-n", "2..3", file_path.to_str().unwrap()]);
+        let mut output = vec![];
+        view_files_to(&args, &mut output).unwrap();
+        fs::remove_file(&file_path).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "2  two\n3  three\n");
+    }
 }
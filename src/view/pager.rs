@@ -0,0 +1,50 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Paging {
+    /// Page only when stdout is a terminal and output exceeds one screen
+    Auto,
+    Always,
+    Never,
+}
+
+/// External pager process, output written to its stdin
+pub struct Pager {
+    child: Child,
+}
+
+impl Pager {
+    /// Spawns `$PAGER` or `less`, returns `None` when paging is off or pager can't be started
+    pub fn spawn(paging: Paging) -> Option<Self> {
+        let command = match paging {
+            Paging::Never => return None,
+            Paging::Auto if !io::stdout().is_terminal() => return None,
+            // less quits by itself when output fits in one screen
+            Paging::Auto => env::var("PAGER").unwrap_or_else(|_| "less -R -F -X".to_string()),
+            Paging::Always => env::var("PAGER").unwrap_or_else(|_| "less -R".to_string()),
+        };
+
+        let mut parts = command.split_whitespace();
+        let program = parts.next()?;
+        let child = Command::new(program).args(parts).stdin(Stdio::piped()).spawn().ok()?;
+
+        Some(Pager { child })
+    }
+
+    pub fn writer(&mut self) -> &mut ChildStdin {
+        self.child.stdin.as_mut().expect("pager stdin is piped")
+    }
+
+    /// closes pager input and waits for user to quit it
+    pub fn wait(mut self) -> io::Result<()> {
+        drop(self.child.stdin.take());
+        self.child.wait()?;
+        Ok(())
+    }
+}
@@ -1,11 +1,11 @@
-use std::{borrow::Cow, str::FromStr};
+use std::{borrow::Cow, collections::HashMap, str::FromStr};
 
 use thiserror::Error;
 
 pub type RangePos = i64;
 pub type RangeCount = u64;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum RangeSpec {
     /// line number "10" "-1"
     Single(RangePos),
@@ -22,16 +22,47 @@ pub enum RangeSpec {
     /// "10+5"
     FromCount(RangePos, RangeCount),
 
-    /// 1,3,5,7..9
+    /// 1,3,5,7..9 or 1..100,~40..60, members are applied left to right,
+    /// complement members exclude lines selected by previous members
     List(Vec<RangeSpec>),
 
     /// ~1..3
     Complement(Box<RangeSpec>),
 
-    ///
+    /// "50%..", "..10%", "25%..75%" or "50%", bounds are `None` when open,
+    /// percentages are resolved to line numbers by `normalize`
+    Percent(Option<Position>, Option<Position>),
+
+    /// "@name", preset from `[ranges]` table of config file, replaced by its spec with
+    /// `resolve_presets` once config is loaded
+    Preset(String),
+
+    /// "-"
+    #[default]
     All,
 }
 
+/// Bound of a range that's either a line number or a percentage of total count
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Line(RangePos),
+    /// "50%", at most 100
+    Percent(RangePos),
+}
+
+impl Position {
+    /// Line number of position, percentage points to the line where that part of input ends
+    fn resolve(self, total: RangeCount) -> RangePos {
+        match self {
+            Position::Line(pos) => RangeSpec::normalize_line(pos, total),
+            Position::Percent(percent) => {
+                let total = total as RangePos;
+                ((total * percent + 99) / 100).max(1)
+            }
+        }
+    }
+}
+
 impl RangeSpec {
     pub fn normalize_line(line: RangePos, total: RangeCount) -> RangePos {
         if line < 0 {
@@ -45,6 +76,64 @@ impl RangeSpec {
         line < 0
     }
 
+    /// whether any position is relative to the end or a percentage, which requires the total
+    /// count to resolve
+    pub fn has_negative(&self) -> bool {
+        match self {
+            RangeSpec::Single(pos) | RangeSpec::From(pos) | RangeSpec::To(pos) => {
+                RangeSpec::is_negative(*pos)
+            }
+            RangeSpec::Range(start, end) => {
+                RangeSpec::is_negative(*start) || RangeSpec::is_negative(*end)
+            }
+            RangeSpec::FromCount(start, _) => RangeSpec::is_negative(*start),
+            RangeSpec::List(specs) => specs.iter().any(|spec| spec.has_negative()),
+            RangeSpec::Complement(spec) => spec.has_negative(),
+            RangeSpec::Percent(..) => true,
+            RangeSpec::Preset(_) | RangeSpec::All => false,
+        }
+    }
+
+    /// Count of lines at the end of input that contain every line in range, `None` when range
+    /// may select lines before them, so ranges like `-100..` need only last 100 lines buffered
+    pub fn tail_window(&self) -> Option<RangeCount> {
+        let from_end = |pos: RangePos| RangeSpec::is_negative(pos).then(|| pos.unsigned_abs());
+
+        match self {
+            RangeSpec::Single(pos) | RangeSpec::From(pos) | RangeSpec::FromCount(pos, _) => {
+                from_end(*pos)
+            }
+            RangeSpec::Range(start, end) => Some(from_end(*start)?.max(from_end(*end)?)),
+            RangeSpec::List(specs) if !specs.is_empty() => {
+                specs.iter().try_fold(0, |window, spec| Some(window.max(spec.tail_window()?)))
+            }
+            _ => None,
+        }
+    }
+
+    /// last line number in range, `None` when range is unbounded
+    pub fn last_line(&self) -> Option<RangePos> {
+        match self {
+            RangeSpec::Single(pos) | RangeSpec::To(pos) | RangeSpec::Range(_, pos) => Some(*pos),
+            RangeSpec::FromCount(start, count) => Some(*start + *count as RangePos - 1),
+            // list starting with exclusion selects everything else
+            RangeSpec::List(specs) if matches!(specs.first(), Some(RangeSpec::Complement(_))) => {
+                None
+            }
+            RangeSpec::List(specs) => {
+                specs.iter().try_fold(RangePos::MIN, |last, spec| match spec {
+                    RangeSpec::Complement(_) => Some(last),
+                    spec => Some(last.max(spec.last_line()?)),
+                })
+            }
+            RangeSpec::From(_)
+            | RangeSpec::Complement(_)
+            | RangeSpec::Percent(..)
+            | RangeSpec::Preset(_)
+            | RangeSpec::All => None,
+        }
+    }
+
     pub fn normalize<'a>(&'a self, total: RangeCount) -> Cow<'a, RangeSpec> {
         match self {
             RangeSpec::Single(pos) => {
@@ -117,7 +206,52 @@ impl RangeSpec {
                     Cow::Borrowed(self)
                 }
             }
-            RangeSpec::All => Cow::Borrowed(self),
+            RangeSpec::Percent(start, end) => {
+                let start = start.map(|start| start.resolve(total));
+                let end = end.map(|end| end.resolve(total));
+                Cow::Owned(match (start, end) {
+                    (Some(start), Some(end)) if start == end => RangeSpec::Single(start),
+                    (Some(start), Some(end)) => RangeSpec::Range(start, end),
+                    (Some(start), None) => RangeSpec::From(start),
+                    (None, Some(end)) => RangeSpec::To(end),
+                    (None, None) => RangeSpec::All,
+                })
+            }
+            RangeSpec::Preset(_) | RangeSpec::All => Cow::Borrowed(self),
+        }
+    }
+
+    /// Positions in order of appearance in spec, repeated selections are repeated.
+    /// Spec should be normalized, positions outside `1..=total` are dropped.
+    pub fn ordered_positions(&self, total: RangeCount) -> Vec<RangePos> {
+        let last = total as RangePos;
+        let between = |start: RangePos, end: RangePos| (start.max(1)..=end.min(last)).collect();
+
+        match self {
+            RangeSpec::Single(pos) => between(*pos, *pos),
+            RangeSpec::Range(start, end) => between(*start, *end),
+            RangeSpec::From(start) => between(*start, last),
+            RangeSpec::To(end) => between(1, *end),
+            RangeSpec::FromCount(start, count) => between(*start, *start + *count as RangePos - 1),
+            RangeSpec::List(specs) => {
+                let mut positions: Vec<RangePos> = match specs.first() {
+                    Some(RangeSpec::Complement(_)) => (1..=last).collect(),
+                    _ => vec![],
+                };
+                for spec in specs {
+                    match spec {
+                        RangeSpec::Complement(excluded) => {
+                            positions.retain(|pos| !excluded.contains(*pos))
+                        }
+                        spec => positions.extend(spec.ordered_positions(total)),
+                    }
+                }
+                positions
+            }
+            RangeSpec::Complement(_)
+            | RangeSpec::Percent(..)
+            | RangeSpec::Preset(_)
+            | RangeSpec::All => (1..=last).filter(|pos| self.contains(*pos)).collect(),
         }
     }
 
@@ -130,16 +264,40 @@ impl RangeSpec {
             RangeSpec::FromCount(start, count) => {
                 *start <= line_no && line_no <= *start + (*count as RangePos - 1)
             }
-            RangeSpec::List(range_specs) => range_specs.iter().any(|spec| spec.contains(line_no)),
+            RangeSpec::List(range_specs) => {
+                let initial = matches!(range_specs.first(), Some(RangeSpec::Complement(_)));
+                range_specs.iter().fold(initial, |selected, spec| match spec {
+                    RangeSpec::Complement(excluded) => selected && !excluded.contains(line_no),
+                    spec => selected || spec.contains(line_no),
+                })
+            }
             RangeSpec::Complement(range_spec) => !range_spec.contains(line_no),
+            // percentages mean nothing until spec is normalized against total count
+            RangeSpec::Percent(..) => false,
+            // presets are resolved right after parsing
+            RangeSpec::Preset(_) => false,
             RangeSpec::All => true,
         }
     }
-}
 
-impl Default for RangeSpec {
-    fn default() -> Self {
-        RangeSpec::All
+    /// Replaces `@name` presets by specs they're defined as in `presets`
+    pub fn resolve_presets(&mut self, presets: &HashMap<String, String>) -> Result<(), String> {
+        match self {
+            RangeSpec::Preset(name) => {
+                let preset = presets
+                    .get(name.as_str())
+                    .ok_or_else(|| format!("unknown range preset '{}'", name))?;
+                *self = preset.parse().map_err(|e: ParseError| {
+                    format!("range preset '{}' = '{}': {}", name, preset, e)
+                })?;
+                Ok(())
+            }
+            RangeSpec::List(specs) => {
+                specs.iter_mut().try_for_each(|spec| spec.resolve_presets(presets))
+            }
+            RangeSpec::Complement(spec) => spec.resolve_presets(presets),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -192,7 +350,7 @@ impl<'a> RangeSpecParser<'a> {
     where
         F: Fn(u8) -> bool,
     {
-        let end = self.find(predicate).unwrap_or(self.input.as_bytes().len());
+        let end = self.find(predicate).unwrap_or(self.input.len());
         &self.input[self.pos..end]
     }
 
@@ -217,7 +375,7 @@ impl<'a> RangeSpecParser<'a> {
     }
 
     fn peek_byte(&self) -> Option<u8> {
-        self.input.as_bytes().get(self.pos).map(|v| *v)
+        self.input.as_bytes().get(self.pos).copied()
     }
 
     fn eof(&self) -> bool {
@@ -258,7 +416,12 @@ impl<'a> RangeSpecParser<'a> {
         let mut ranges: Vec<RangeSpec> = Vec::new();
 
         while !self.eof() {
-            let range = self.parse_basic()?;
+            let range = if self.start_with("~") {
+                self.take("~")?;
+                RangeSpec::Complement(Box::new(self.parse_basic()?))
+            } else {
+                self.parse_basic()?
+            };
             ranges.push(range);
             if let Some(byte) = self.peek_byte() {
                 if byte == b',' {
@@ -282,10 +445,15 @@ impl<'a> RangeSpecParser<'a> {
         };
 
         let (sign, mut value) = match byte {
+            b'-' if self.peek(3) == "-0x" => {
+                self.advance(1);
+                return Ok(-self.parse_hex_number()?);
+            }
             b'-' => {
                 self.advance(1);
                 (-1, 0 as RangePos)
             }
+            b'0' if self.start_with("0x") => return self.parse_hex_number(),
             b'0' => {
                 self.advance(1);
                 return Ok(0);
@@ -294,7 +462,7 @@ impl<'a> RangeSpecParser<'a> {
                 self.advance(1);
                 (
                     1,
-                    byte_to_digit(byte).expect(&format!("{} is ascii digit", byte)),
+                    byte_to_digit(byte).unwrap_or_else(|_| panic!("{} is ascii digit", byte)),
                 )
             }
             _ => {
@@ -315,19 +483,69 @@ impl<'a> RangeSpecParser<'a> {
             value = value
                 .checked_mul(10)
                 .and_then(|v| v.checked_add(byte_to_digit(byte).expect("{byte} is ascii digit")))
-                .ok_or_else(|| ParseError::NumberTooLarge(number_text))?;
+                .ok_or(ParseError::NumberTooLarge(number_text))?;
             self.advance(1);
         }
 
         Ok(sign * value)
     }
 
+    /// number like "0x1ff", handy for byte offsets
+    fn parse_hex_number(&mut self) -> Result<RangePos, ParseError> {
+        self.take("0x")?;
+        let digits = self.peek_until(|b| !b.is_ascii_hexdigit());
+        if digits.is_empty() {
+            return Err(ParseError::InvalidNumber(
+                self.peek_until_whitespace().to_string(),
+            ));
+        }
+
+        let value = RangePos::from_str_radix(digits, 16)
+            .map_err(|_| ParseError::NumberTooLarge(format!("0x{}", digits)))?;
+        self.advance(digits.len());
+        Ok(value)
+    }
+
     fn parse_to(&mut self) -> Result<RangeSpec, ParseError> {
         self.take("..")?;
         let val = self.parse_number()?;
+        if self.start_with("%") {
+            let end = self.parse_percent(val)?;
+            return Ok(RangeSpec::Percent(None, Some(end)));
+        }
         Ok(RangeSpec::To(val))
     }
 
+    /// takes '%' after already parsed number
+    fn parse_percent(&mut self, value: RangePos) -> Result<Position, ParseError> {
+        self.take("%")?;
+        if (0..=100).contains(&value) {
+            Ok(Position::Percent(value))
+        } else {
+            Err(ParseError::InvalidPercent(value))
+        }
+    }
+
+    /// range with percentage start like "50%..", "50%..-1" or "50%"
+    fn parse_percent_range(&mut self, start: RangePos) -> Result<RangeSpec, ParseError> {
+        let start = self.parse_percent(start)?;
+        if !self.start_with("..") {
+            return Ok(RangeSpec::Percent(Some(start), Some(start)));
+        }
+
+        self.take("..")?;
+        if self.peek_until_whitespace().is_empty() || self.start_with(",") {
+            return Ok(RangeSpec::Percent(Some(start), None));
+        }
+        let end = self.parse_number()?;
+        let end = if self.start_with("%") {
+            self.parse_percent(end)?
+        } else {
+            Position::Line(end)
+        };
+        Ok(RangeSpec::Percent(Some(start), Some(end)))
+    }
+
     fn parse_basic(&mut self) -> Result<RangeSpec, ParseError> {
         let Some(byte) = self.peek_byte() else {
             return Err(ParseError::EarlyEof(
@@ -336,9 +554,13 @@ impl<'a> RangeSpecParser<'a> {
         };
 
         match byte {
-            b'-' | b'1'..=b'9' => {
+            b'0' if !self.start_with("0x") => Err(ParseError::InvalidNumber(
+                self.peek_until_whitespace().to_string(),
+            )),
+            b'-' | b'0'..=b'9' => {
                 let start = self.parse_number()?;
                 match self.peek_byte() {
+                    Some(b'%') => self.parse_percent_range(start),
                     Some(b'.') => {
                         self.take("..")?;
                         let text = self.peek_until_whitespace();
@@ -346,6 +568,10 @@ impl<'a> RangeSpecParser<'a> {
                             return Ok(RangeSpec::From(start));
                         }
                         let end = self.parse_number()?;
+                        if self.start_with("%") {
+                            let end = self.parse_percent(end)?;
+                            return Ok(RangeSpec::Percent(Some(Position::Line(start)), Some(end)));
+                        }
                         Ok(RangeSpec::Range(start, end))
                     }
                     Some(b'+') => {
@@ -385,6 +611,9 @@ pub enum ParseError {
     #[error("invalid negative range count: {0}")]
     InvalidRangeCount(i64),
 
+    #[error("percentage should be between 0 and 100, get {0}%")]
+    InvalidPercent(i64),
+
     #[error("unexpected input, expected: {}, actual: {}", expected, actual)]
     UnexpectedInput { expected: String, actual: String },
 
@@ -440,6 +669,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hex_numbers() {
+        assert_eq!(
+            "0x100..0x1ff".parse::<RangeSpec>().unwrap(),
+            RangeSpec::Range(256, 511)
+        );
+        assert_eq!(
+            "-0x10..".parse::<RangeSpec>().unwrap(),
+            RangeSpec::From(-16)
+        );
+        assert_eq!(
+            "0x10+4".parse::<RangeSpec>().unwrap(),
+            RangeSpec::FromCount(16, 4)
+        );
+        assert!("0x".parse::<RangeSpec>().is_err());
+        assert!("0xg".parse::<RangeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_list_with_complement_members() {
+        let spec = "1..10,~4..6,5".parse::<RangeSpec>().unwrap();
+        assert_eq!(
+            spec,
+            RangeSpec::List(vec![
+                RangeSpec::Range(1, 10),
+                RangeSpec::Complement(Box::new(RangeSpec::Range(4, 6))),
+                RangeSpec::Single(5),
+            ])
+        );
+        let selected: Vec<_> = (1..=12).filter(|pos| spec.contains(*pos)).collect();
+        assert_eq!(selected, vec![1, 2, 3, 5, 7, 8, 9, 10]);
+        assert_eq!(spec.last_line(), Some(10));
+        assert_eq!(spec.ordered_positions(12), vec![1, 2, 3, 7, 8, 9, 10, 5]);
+
+        // leading complement still applies to the whole list
+        let spec = "~1..3,5".parse::<RangeSpec>().unwrap();
+        assert!(spec.contains(4) && !spec.contains(5));
+
+        // list starting with exclusion, as produced by merged -n values
+        let spec = RangeSpec::List(vec![
+            RangeSpec::Complement(Box::new(RangeSpec::Range(2, 3))),
+            RangeSpec::Single(3),
+        ]);
+        let selected: Vec<_> = (1..=5).filter(|pos| spec.contains(*pos)).collect();
+        assert_eq!(selected, vec![1, 3, 4, 5]);
+        assert_eq!(spec.last_line(), None);
+    }
+
+    #[test]
+    fn test_percent_positions() {
+        assert_eq!(
+            "50%..".parse::<RangeSpec>().unwrap(),
+            RangeSpec::Percent(Some(Position::Percent(50)), None)
+        );
+        assert_eq!(
+            "..10%".parse::<RangeSpec>().unwrap(),
+            RangeSpec::Percent(None, Some(Position::Percent(10)))
+        );
+        assert_eq!(
+            "5..10%".parse::<RangeSpec>().unwrap(),
+            RangeSpec::Percent(Some(Position::Line(5)), Some(Position::Percent(10)))
+        );
+        assert_eq!(
+            "150%".parse::<RangeSpec>(),
+            Err(ParseError::InvalidPercent(150))
+        );
+
+        let spec = "50%..".parse::<RangeSpec>().unwrap();
+        assert!(spec.has_negative());
+        assert_eq!(*spec.normalize(200), RangeSpec::From(100));
+        assert_eq!(
+            *"..10%".parse::<RangeSpec>().unwrap().normalize(15),
+            RangeSpec::To(2)
+        );
+        assert_eq!(
+            *"25%..75%".parse::<RangeSpec>().unwrap().normalize(100),
+            RangeSpec::Range(25, 75)
+        );
+        assert_eq!(
+            *"100%".parse::<RangeSpec>().unwrap().normalize(7),
+            RangeSpec::Single(7)
+        );
+        assert_eq!(
+            *"1,50%..".parse::<RangeSpec>().unwrap().normalize(10),
+            RangeSpec::List(vec![RangeSpec::Single(1), RangeSpec::From(5)])
+        );
+    }
+
+    #[test]
+    fn test_tail_window() {
+        assert_eq!(
+            "-100..".parse::<RangeSpec>().unwrap().tail_window(),
+            Some(100)
+        );
+        assert_eq!(
+            "-5,-20..-10".parse::<RangeSpec>().unwrap().tail_window(),
+            Some(20)
+        );
+        assert_eq!("-3+2".parse::<RangeSpec>().unwrap().tail_window(), Some(3));
+        assert_eq!("1,-5..".parse::<RangeSpec>().unwrap().tail_window(), None);
+        assert_eq!("..-5".parse::<RangeSpec>().unwrap().tail_window(), None);
+        assert_eq!("~-5".parse::<RangeSpec>().unwrap().tail_window(), None);
+    }
+
+    #[test]
+    fn test_has_negative() {
+        assert!(!RangeSpec::All.has_negative());
+        assert!(!"10..20".parse::<RangeSpec>().unwrap().has_negative());
+        assert!("-10..".parse::<RangeSpec>().unwrap().has_negative());
+        assert!("1,3,..-2".parse::<RangeSpec>().unwrap().has_negative());
+        assert!("~-5".parse::<RangeSpec>().unwrap().has_negative());
+    }
+
+    #[test]
+    fn test_last_line() {
+        assert_eq!("10..20".parse::<RangeSpec>().unwrap().last_line(), Some(20));
+        assert_eq!("10+5".parse::<RangeSpec>().unwrap().last_line(), Some(14));
+        assert_eq!("1,30,5".parse::<RangeSpec>().unwrap().last_line(), Some(30));
+        assert_eq!("1,30..".parse::<RangeSpec>().unwrap().last_line(), None);
+        assert_eq!("~1..5".parse::<RangeSpec>().unwrap().last_line(), None);
+    }
+
+    #[test]
+    fn test_ordered_positions() {
+        let spec = "4..5,1..2,4".parse::<RangeSpec>().unwrap();
+        assert_eq!(spec.ordered_positions(10), vec![4, 5, 1, 2, 4]);
+
+        let spec = "8..".parse::<RangeSpec>().unwrap();
+        assert_eq!(spec.ordered_positions(10), vec![8, 9, 10]);
+
+        let spec = "~2..9".parse::<RangeSpec>().unwrap();
+        assert_eq!(spec.ordered_positions(10), vec![1, 10]);
+    }
+
     #[test]
     fn test_parse_error_empty() {
         let result = "".parse::<RangeSpec>();
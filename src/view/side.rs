@@ -0,0 +1,104 @@
+use std::{io::Write, path::Path};
+
+use terminal_size::{Width, terminal_size};
+use unicode_width::UnicodeWidthChar;
+
+use super::args::ViewArgs;
+use super::decompress;
+use super::error::{Result, ViewError};
+use super::lines::LineReader;
+use super::range::{RangeCount, RangePos};
+use crate::paths;
+
+/// width used when output isn't a terminal
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// separator between the two columns
+const COLUMN_SEPARATOR: &str = " | ";
+
+/// Prints selected lines of two files next to each other in columns of equal width
+pub fn view_side_by_side<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
+    let [left_path, right_path] = args.file_paths.as_slice() else {
+        return Err(ViewError::SideBySideRequiresTwoFiles);
+    };
+
+    let total_width = terminal_size().map_or(DEFAULT_TERMINAL_WIDTH, |(Width(w), _)| w as usize);
+    let width = total_width.saturating_sub(COLUMN_SEPARATOR.len()) / 2;
+
+    let left = read_selected_lines(left_path, args)?;
+    let right = read_selected_lines(right_path, args)?;
+
+    if !args.quite {
+        let left_header = paths::native(left_path);
+        let right_header = paths::native(right_path);
+        write_row(&left_header, &right_header, width, writer)?;
+        write_row(&"-".repeat(width), &"-".repeat(width), width, writer)?;
+    }
+
+    for i in 0..left.len().max(right.len()) {
+        let left_line = left.get(i).map_or("", String::as_str);
+        let right_line = right.get(i).map_or("", String::as_str);
+        write_row(left_line, right_line, width, writer)?;
+    }
+
+    Ok(())
+}
+
+fn read_selected_lines(file_path: &Path, args: &ViewArgs) -> Result<Vec<String>> {
+    let (mut reader, _) = decompress::open_file(file_path)?;
+    let mut line_reader = LineReader::new(&mut reader, args.max_line_bytes);
+    let lines = line_reader.read_all_lines()?;
+    line_reader.report_truncation();
+
+    let line_ranges = args.line_ranges();
+    let ranges = line_ranges.normalize(lines.len() as RangeCount);
+
+    Ok(lines
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| ranges.contains(*i as RangePos + 1))
+        .map(|(_, line)| line.trim_end_matches(['\r', '\n']).replace('\t', "    "))
+        .collect())
+}
+
+fn write_row<W: Write>(left: &str, right: &str, width: usize, writer: &mut W) -> Result<()> {
+    let left = fit_column(left, width);
+    let (right, _) = cut_to_width(right, width);
+    writeln!(writer, "{}{}{}", left, COLUMN_SEPARATOR, right.trim_end())?;
+    Ok(())
+}
+
+/// Cuts or pads text to exactly `width` terminal columns, wide characters like CJK take two
+fn fit_column(text: &str, width: usize) -> String {
+    let (cut, cut_width) = cut_to_width(text, width);
+    let mut column = cut.to_string();
+    column.extend(std::iter::repeat_n(' ', width - cut_width));
+    column
+}
+
+/// Longest start of text fitting in `width` columns, with its width
+fn cut_to_width(text: &str, width: usize) -> (&str, usize) {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width {
+            return (&text[..i], used);
+        }
+        used += char_width;
+    }
+    (text, used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_column() {
+        assert_eq!(fit_column("abc", 5), "abc  ");
+        assert_eq!(fit_column("abcdef", 4), "abcd");
+        assert_eq!(fit_column("日本語", 2), "日");
+        assert_eq!(fit_column("日本語", 5), "日本 ");
+        assert_eq!(fit_column("a日", 4), "a日 ");
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    fs,
+    io::Write,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use super::args::ViewArgs;
+use super::error::Result;
+use crate::cancel;
+use crate::report;
+
+/// interval between checks for file changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// modification time and size of every watched file, `None` for missing files
+type Snapshot = Vec<Option<(SystemTime, u64)>>;
+
+/// Re-renders files forever, whenever any of them changes on disk
+pub fn watch_files<W: Write>(args: &ViewArgs, writer: &mut W) -> Result<()> {
+    let mut last_snapshot = None;
+
+    loop {
+        cancel::check()?;
+        let snapshot = take_snapshot(args);
+        if last_snapshot.as_ref() != Some(&snapshot) {
+            // clear screen and move cursor to top left corner
+            write!(writer, "\x1b[2J\x1b[H")?;
+            if let Err(e) = super::view_files_to(args, writer) {
+                report::file_error("view", e);
+            }
+            writer.flush()?;
+            last_snapshot = Some(snapshot);
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn take_snapshot(args: &ViewArgs) -> Snapshot {
+    args.file_paths
+        .iter()
+        .map(|file_path| {
+            let metadata = fs::metadata(file_path).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}